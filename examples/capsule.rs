@@ -0,0 +1,43 @@
+extern crate glium;
+extern crate glium_shapes;
+mod common;
+use glium::Surface;
+use glium_shapes::shape::ShapeBuilder;
+
+fn main() {
+    // Setup glium display and shared example data (program, uniforms, draw params, etc)
+    let (mut ev, display, data) = common::setup();
+
+    // KEY POINT: Use a CapsuleBuilder to build a new capsule.
+    // Use the methods on the builder object to customise the resultant
+    // shape. In this case we will create a capsule with a radius of 2 and
+    // a cylinder length of 4, with its base located at the origin.
+    let capsule = glium_shapes::capsule::CapsuleBuilder::new()
+        .with_radius(2.0)
+        .with_length(4.0)
+        .with_divisions(32, 8, 4)
+        .translate(0.0, 4.0, 0.0)
+        .build(&display)
+        .expect("Failed to build capsule shape");
+
+    // Loop until the user closes the display window.
+    while common::process_events(&mut ev) {
+        // Begin a new frame.
+        let (mut frame, uniforms) = common::begin_frame(&display);
+
+        // KEY POINT: Draw the capsule shape by passing it as a source
+        // of both vertices and indices to glium.
+        frame
+            .draw(
+                &capsule,
+                &capsule,
+                &data.program,
+                &uniforms,
+                &data.draw_params,
+            )
+            .expect("Failed to draw capsule shape");
+
+        // Finish the frame.
+        common::end_frame(frame);
+    }
+}
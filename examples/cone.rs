@@ -0,0 +1,38 @@
+extern crate glium;
+extern crate glium_shapes;
+mod common;
+use glium::Surface;
+use glium_shapes::shape::ShapeBuilder;
+
+fn main() {
+    // Setup glium display and shared example data (program, uniforms, draw params, etc)
+    let (mut ev, display, data) = common::setup();
+
+    // KEY POINT: Use a ConeBuilder to build a new cone.
+    // Use the methods on the builder object to customise the resultant
+    // shape. In this case we will create a truncated cone with a base
+    // radius of 4, a top radius of 2, and a height of 6, with its base
+    // located at the origin.
+    let cone = glium_shapes::cone::ConeBuilder::new()
+        .with_base_radius(2.0)
+        .with_top_radius(1.0)
+        .scale(2.0, 3.0, 2.0)
+        .translate(0.0, 3.0, 0.0)
+        .build(&display)
+        .expect("Failed to build cone shape");
+
+    // Loop until the user closes the display window.
+    while common::process_events(&mut ev) {
+        // Begin a new frame.
+        let (mut frame, uniforms) = common::begin_frame(&display);
+
+        // KEY POINT: Draw the cone shape by passing it as a source
+        // of both vertices and indices to glium.
+        frame
+            .draw(&cone, &cone, &data.program, &uniforms, &data.draw_params)
+            .expect("Failed to draw cone shape");
+
+        // Finish the frame.
+        common::end_frame(frame);
+    }
+}
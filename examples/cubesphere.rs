@@ -0,0 +1,43 @@
+extern crate glium;
+extern crate glium_shapes;
+mod common;
+use glium::Surface;
+use glium_shapes::shape::ShapeBuilder;
+
+fn main() {
+    // Setup glium display and shared example data (program, uniforms, draw params, etc)
+    let (mut ev, display, data) = common::setup();
+
+    // KEY POINT: Use a CubeSphereBuilder to build a new cubesphere.
+    // Use the methods on the builder object to customise the resultant
+    // shape. In this case we will create an equal-area cube-sphere with
+    // a radius of 3, with its base located at the origin.
+    let cubesphere = glium_shapes::cubesphere::CubeSphereBuilder::new()
+        .with_divisions(12)
+        .with_projection(glium_shapes::cubesphere::CubeSphereProjection::EqualArea)
+        .scale(3.0, 3.0, 3.0)
+        .translate(0.0, 3.0, 0.0)
+        .build(&display)
+        .expect("Failed to build cubesphere shape");
+
+    // Loop until the user closes the display window.
+    while common::process_events(&mut ev) {
+        // Begin a new frame.
+        let (mut frame, uniforms) = common::begin_frame(&display);
+
+        // KEY POINT: Draw the cubesphere shape by passing it as a source
+        // of both vertices and indices to glium.
+        frame
+            .draw(
+                &cubesphere,
+                &cubesphere,
+                &data.program,
+                &uniforms,
+                &data.draw_params,
+            )
+            .expect("Failed to draw cubesphere shape");
+
+        // Finish the frame.
+        common::end_frame(frame);
+    }
+}
@@ -0,0 +1,43 @@
+extern crate glium;
+extern crate glium_shapes;
+mod common;
+use glium::Surface;
+use glium_shapes::shape::ShapeBuilder;
+
+fn main() {
+    // Setup glium display and shared example data (program, uniforms, draw params, etc)
+    let (mut ev, display, data) = common::setup();
+
+    // KEY POINT: Use a CylinderBuilder to build a new cylinder.
+    // Use the methods on the builder object to customise the resultant
+    // shape. In this case we will create an open-ended cylinder with
+    // a radius of 2 and a height of 6, with its base located at the origin.
+    let cylinder = glium_shapes::cylinder::CylinderBuilder::new()
+        .with_divisions(32, 4)
+        .open_ended()
+        .scale(2.0, 3.0, 2.0)
+        .translate(0.0, 3.0, 0.0)
+        .build(&display)
+        .expect("Failed to build cylinder shape");
+
+    // Loop until the user closes the display window.
+    while common::process_events(&mut ev) {
+        // Begin a new frame.
+        let (mut frame, uniforms) = common::begin_frame(&display);
+
+        // KEY POINT: Draw the cylinder shape by passing it as a source
+        // of both vertices and indices to glium.
+        frame
+            .draw(
+                &cylinder,
+                &cylinder,
+                &data.program,
+                &uniforms,
+                &data.draw_params,
+            )
+            .expect("Failed to draw cylinder shape");
+
+        // Finish the frame.
+        common::end_frame(frame);
+    }
+}
@@ -0,0 +1,43 @@
+extern crate cgmath;
+extern crate glium;
+extern crate glium_shapes;
+mod common;
+use glium::Surface;
+use glium_shapes::shape::ShapeBuilder;
+
+fn main() {
+    // Setup glium display and shared example data (program, uniforms, draw params, etc)
+    let (mut ev, display, data) = common::setup();
+
+    // KEY POINT: Use a DiscBuilder to build a new Disc object.
+    // Use the methods on the builder object to customise the resultant
+    // shape. In this case we will create a three-quarter annulus with
+    // 48 segments at the origin.
+    //
+    // NOTE: The disc is set up to face the negative Z-axis by default,
+    // but our example camera also looks down the negative Z-axis, and
+    // so we rotate the disc 180 degrees in Y to face the camera such
+    // that it doesn't get backface culled.
+    let disc = glium_shapes::disc::DiscBuilder::new()
+        .with_divisions(48)
+        .with_inner_radius(0.5)
+        .with_angles(0.0, 1.5 * std::f32::consts::PI)
+        .rotate_y(std::f32::consts::PI)
+        .build(&display)
+        .expect("Failed to build disc shape");
+
+    // Loop until the user closes the display window.
+    while common::process_events(&mut ev) {
+        // Begin a new frame.
+        let (mut frame, uniforms) = common::begin_frame(&display);
+
+        // KEY POINT: Draw the disc shape by passing it as a source
+        // of both vertices and indices to glium.
+        frame
+            .draw(&disc, &disc, &data.program, &uniforms, &data.draw_params)
+            .expect("Failed to draw disc shape");
+
+        // Finish the frame.
+        common::end_frame(frame);
+    }
+}
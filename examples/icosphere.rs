@@ -0,0 +1,43 @@
+extern crate glium;
+extern crate glium_shapes;
+mod common;
+use glium::Surface;
+use glium_shapes::shape::ShapeBuilder;
+
+fn main() {
+    // Setup glium display and shared example data (program, uniforms, draw params, etc)
+    let (mut ev, display, data) = common::setup();
+
+    // KEY POINT: Use an IcosphereBuilder to build a new icosphere.
+    // Use the methods on the builder object to customise the resultant
+    // shape. In this case we will create a smooth-shaded icosphere with
+    // a radius of 3, with its base located at the origin.
+    let icosphere = glium_shapes::icosphere::IcosphereBuilder::new()
+        .with_subdivisions(3)
+        .with_shading(glium_shapes::shading::Shading::Smooth)
+        .scale(3.0, 3.0, 3.0)
+        .translate(0.0, 3.0, 0.0)
+        .build(&display)
+        .expect("Failed to build icosphere shape");
+
+    // Loop until the user closes the display window.
+    while common::process_events(&mut ev) {
+        // Begin a new frame.
+        let (mut frame, uniforms) = common::begin_frame(&display);
+
+        // KEY POINT: Draw the icosphere shape by passing it as a source
+        // of both vertices and indices to glium.
+        frame
+            .draw(
+                &icosphere,
+                &icosphere,
+                &data.program,
+                &uniforms,
+                &data.draw_params,
+            )
+            .expect("Failed to draw icosphere shape");
+
+        // Finish the frame.
+        common::end_frame(frame);
+    }
+}
@@ -0,0 +1,43 @@
+extern crate cgmath;
+extern crate glium;
+extern crate glium_shapes;
+mod common;
+use glium::Surface;
+use glium_shapes::shape::ShapeBuilder;
+
+fn main() {
+    // Setup glium display and shared example data (program, uniforms, draw params, etc)
+    let (mut ev, display, data) = common::setup();
+
+    // KEY POINT: Use a PlaneBuilder to build a new Plane object.
+    // Use the methods on the builder object to customise the resultant
+    // shape. In this case we will create a plane subdivided into a 16x16
+    // grid at the origin, indexed such that neighbouring cells share their
+    // vertices.
+    //
+    // NOTE: The plane is set up to face the negative Z-axis by default,
+    // but our example camera also looks down the negative Z-axis, and
+    // so we rotate the plane 180 degrees in Y to face the camera such
+    // that it doesn't get backface culled.
+    let plane = glium_shapes::plane::PlaneBuilder::new()
+        .with_divisions(16, 16)
+        .indexed()
+        .rotate_y(std::f32::consts::PI)
+        .build(&display)
+        .expect("Failed to build plane shape");
+
+    // Loop until the user closes the display window.
+    while common::process_events(&mut ev) {
+        // Begin a new frame.
+        let (mut frame, uniforms) = common::begin_frame(&display);
+
+        // KEY POINT: Draw the plane shape by passing it as a source
+        // of both vertices and indices to glium.
+        frame
+            .draw(&plane, &plane, &data.program, &uniforms, &data.draw_params)
+            .expect("Failed to draw plane shape");
+
+        // Finish the frame.
+        common::end_frame(frame);
+    }
+}
@@ -0,0 +1,45 @@
+extern crate glium;
+extern crate glium_shapes;
+mod common;
+use glium::Surface;
+use glium_shapes::shape::ShapeBuilder;
+
+fn main() {
+    // Setup glium display and shared example data (program, uniforms, draw params, etc)
+    let (mut ev, display, data) = common::setup();
+
+    // KEY POINT: Use a PyramidBuilder to build a new pyramid.
+    // Use the methods on the builder object to customise the resultant
+    // shape. In this case we will create an oblique hexagonal pyramid
+    // with a base radius of 4 and a height of 6, with its base located
+    // at the origin.
+    let pyramid = glium_shapes::pyramid::PyramidBuilder::new()
+        .with_sides(6)
+        .with_base_radius(4.0)
+        .with_height(6.0)
+        .with_apex_offset(1.0, 0.0)
+        .translate(0.0, 3.0, 0.0)
+        .build(&display)
+        .expect("Failed to build pyramid shape");
+
+    // Loop until the user closes the display window.
+    while common::process_events(&mut ev) {
+        // Begin a new frame.
+        let (mut frame, uniforms) = common::begin_frame(&display);
+
+        // KEY POINT: Draw the pyramid shape by passing it as a source
+        // of both vertices and indices to glium.
+        frame
+            .draw(
+                &pyramid,
+                &pyramid,
+                &data.program,
+                &uniforms,
+                &data.draw_params,
+            )
+            .expect("Failed to draw pyramid shape");
+
+        // Finish the frame.
+        common::end_frame(frame);
+    }
+}
@@ -3,6 +3,7 @@ extern crate glium;
 extern crate glium_shapes;
 mod common;
 use glium::Surface;
+use glium_shapes::shape::ShapeBuilder;
 
 fn main() {
     // Setup glium display and shared example data (program, uniforms, draw params, etc)
@@ -2,6 +2,7 @@ extern crate glium;
 extern crate glium_shapes;
 mod common;
 use glium::Surface;
+use glium_shapes::shape::ShapeBuilder;
 
 fn main() {
     // Setup glium display and shared example data (program, uniforms, draw params, etc)
@@ -10,8 +11,11 @@ fn main() {
     // KEY POINT: Use a SphereBuilder to build a new sphere.
     // Use the methods on the builder object to customise the resultant
     // shape. In this case we will create a 2x3x4 sphere with its base
-    // located at the origin.
+    // located at the origin, with smooth normals and stored as indexed
+    // geometry.
     let sphere = glium_shapes::sphere::SphereBuilder::new()
+        .with_shading(glium_shapes::shading::Shading::Smooth)
+        .indexed()
         .scale(2.0, 3.0, 4.0)
         .translate(0.0, 1.5, 0.0)
         .build(&display)
@@ -0,0 +1,36 @@
+extern crate glium;
+extern crate glium_shapes;
+mod common;
+use glium::Surface;
+use glium_shapes::shape::ShapeBuilder;
+
+fn main() {
+    // Setup glium display and shared example data (program, uniforms, draw params, etc)
+    let (mut ev, display, data) = common::setup();
+
+    // KEY POINT: Use a TorusBuilder to build a new torus.
+    // Use the methods on the builder object to customise the resultant
+    // shape. In this case we will create a torus with a major radius of 4
+    // and a minor radius of 1, rotated to face the camera.
+    let torus = glium_shapes::torus::TorusBuilder::new()
+        .with_radii(4.0, 1.0)
+        .with_divisions(32, 16)
+        .rotate_x(std::f32::consts::PI / 2.0)
+        .build(&display)
+        .expect("Failed to build torus shape");
+
+    // Loop until the user closes the display window.
+    while common::process_events(&mut ev) {
+        // Begin a new frame.
+        let (mut frame, uniforms) = common::begin_frame(&display);
+
+        // KEY POINT: Draw the torus shape by passing it as a source
+        // of both vertices and indices to glium.
+        frame
+            .draw(&torus, &torus, &data.program, &uniforms, &data.draw_params)
+            .expect("Failed to draw torus shape");
+
+        // Finish the frame.
+        common::end_frame(frame);
+    }
+}
@@ -1,34 +1,22 @@
 //! A module for constructing axes locator shapes.
 
 extern crate cgmath;
+#[cfg(feature = "glium")]
 extern crate glium;
 
 use self::cgmath::*;
 use crate::errors::ShapeCreationError;
+use crate::index;
+#[cfg(feature = "glium")]
+use crate::mesh::Mesh;
+use crate::shape::{ShapeBuilder, ShapeOptions, VertexColor};
 use crate::vertex::Vertex;
 
 /// A set of orthogonal `Axes` lines.
 ///
 /// This object is constructed using a `AxesBuilder` object.
-pub struct Axes {
-    vertices: glium::vertex::VertexBufferAny,
-}
-
-/// Allows an `Axes` object to be passed as a source of vertices.
-impl<'a> From<&'a Axes> for glium::vertex::VerticesSource<'a> {
-    fn from(axes: &'a Axes) -> glium::vertex::VerticesSource<'a> {
-        (&axes.vertices).into()
-    }
-}
-
-/// Allows an `Axes` object to be passed as a source of indices.
-impl<'a> Into<glium::index::IndicesSource<'a>> for &'a Axes {
-    fn into(self) -> glium::index::IndicesSource<'a> {
-        glium::index::IndicesSource::NoIndices {
-            primitives: glium::index::PrimitiveType::LinesList,
-        }
-    }
-}
+#[cfg(feature = "glium")]
+pub type Axes = Mesh;
 
 /// Responsible for building and returning an `Axes` object.
 ///
@@ -40,17 +28,11 @@ impl<'a> Into<glium::index::IndicesSource<'a>> for &'a Axes {
 /// a right-handed coordinate system. Vertex normals define the normalised
 /// direction of their respective axis line. Vertex texture coordinates encode
 /// end point in the U coordinate (a value of 0 or 1), and the axis number in
-/// the V coordinate (a value of 0, 1, or 2).
+/// the V coordinate (a value of 0, 1, or 2). Colours can optionally be given to
+/// each axis, which are then stored in the vertices directly.
+#[derive(Default)]
 pub struct AxesBuilder {
-    matrix: cgmath::Matrix4<f32>,
-}
-
-impl Default for AxesBuilder {
-    fn default() -> AxesBuilder {
-        AxesBuilder {
-            matrix: cgmath::Matrix4::<f32>::identity(),
-        }
-    }
+    options: ShapeOptions,
 }
 
 impl AxesBuilder {
@@ -59,104 +41,32 @@ impl AxesBuilder {
         Default::default()
     }
 
-    /// Apply a scaling transformation to the shape.
-    ///
-    /// The `scale`, `translate`, and `rotate` functions accumulate, and are
-    /// not commutative. The transformation functions are intended to provide
-    /// flexibility in model-space. For per-instance world-space transformations,
-    /// one should prefer to share as few shapes as possible across multiple
-    /// instances, and instead rely on uniform constants in the shader and/or
-    /// instanced drawing.
-    pub fn scale(mut self, x: f32, y: f32, z: f32) -> Self {
-        self.matrix = cgmath::Matrix4::from_nonuniform_scale(x, y, z) * self.matrix;
-        self
-    }
-
-    /// Apply a translation transformation to the shape.
-    ///
-    /// The `scale`, `translate`, and `rotate` functions accumulate, and are
-    /// not commutative. The transformation functions are intended to provide
-    /// flexibility in model-space. For per-instance world-space transformations,
-    /// one should prefer to share as few shapes as possible across multiple
-    /// instances, and instead rely on uniform constants in the shader and/or
-    /// instanced drawing.
-    pub fn translate(mut self, x: f32, y: f32, z: f32) -> Self {
-        self.matrix = cgmath::Matrix4::from_translation([x, y, z].into()) * self.matrix;
-        self
-    }
-
-    /// Apply a rotation transformation to the shape about the x-axis.
-    ///
-    /// The `scale`, `translate`, and `rotate` functions accumulate, and are
-    /// not commutative. The transformation functions are intended to provide
-    /// flexibility in model-space. For per-instance world-space transformations,
-    /// one should prefer to share as few shapes as possible across multiple
-    /// instances, and instead rely on uniform constants in the shader and/or
-    /// instanced drawing.
-    pub fn rotate_x(mut self, radians: f32) -> Self {
-        self.matrix = cgmath::Matrix4::<f32>::from(cgmath::Matrix3::<f32>::from_angle_x(
-            cgmath::Rad::<f32>(radians),
-        )) * self.matrix;
+    /// Specify a colour for each of the x, y, and z axis lines (e.g. red, green,
+    /// and blue), in which case its vertices use the `ColorVertex` layout (or the
+    /// `ColorTangentVertex` layout when tangents are also requested). The axis
+    /// number remains encoded in the texture coordinates regardless.
+    pub fn with_axis_colors(mut self, x: [f32; 4], y: [f32; 4], z: [f32; 4]) -> Self {
+        self.options.color = Some(VertexColor::PerAxis([x, y, z]));
         self
     }
+}
 
-    /// Apply a rotation transformation to the shape about the y-axis.
-    ///
-    /// The `scale`, `translate`, and `rotate` functions accumulate, and are
-    /// not commutative. The transformation functions are intended to provide
-    /// flexibility in model-space. For per-instance world-space transformations,
-    /// one should prefer to share as few shapes as possible across multiple
-    /// instances, and instead rely on uniform constants in the shader and/or
-    /// instanced drawing.
-    pub fn rotate_y(mut self, radians: f32) -> Self {
-        self.matrix = cgmath::Matrix4::<f32>::from(cgmath::Matrix3::<f32>::from_angle_y(
-            cgmath::Rad::<f32>(radians),
-        )) * self.matrix;
-        self
+impl ShapeBuilder for AxesBuilder {
+    fn options(&self) -> &ShapeOptions {
+        &self.options
     }
 
-    /// Apply a rotation transformation to the shape about the z-axis.
-    ///
-    /// The `scale`, `translate`, and `rotate` functions accumulate, and are
-    /// not commutative. The transformation functions are intended to provide
-    /// flexibility in model-space. For per-instance world-space transformations,
-    /// one should prefer to share as few shapes as possible across multiple
-    /// instances, and instead rely on uniform constants in the shader and/or
-    /// instanced drawing.
-    pub fn rotate_z(mut self, radians: f32) -> Self {
-        self.matrix = cgmath::Matrix4::<f32>::from(cgmath::Matrix3::<f32>::from_angle_z(
-            cgmath::Rad::<f32>(radians),
-        )) * self.matrix;
-        self
+    fn options_mut(&mut self) -> &mut ShapeOptions {
+        &mut self.options
     }
 
-    /// Build a new `Axes` object.
-    pub fn build<F>(self, display: &F) -> Result<Axes, ShapeCreationError>
-    where
-        F: glium::backend::Facade,
-    {
-        let vertices =
-            glium::vertex::VertexBuffer::<Vertex>::new(display, &self.build_vertices()?)?;
-
-        Ok(Axes {
-            vertices: glium::vertex::VertexBufferAny::from(vertices),
-        })
+    fn primitives(&self) -> index::PrimitiveType {
+        index::PrimitiveType::LinesList
     }
 
-    /// Build the axes vertices and return them in a vector.
-    ///
-    /// Useful if you wish to do other things with the vertices besides constructing
-    /// a `Axes` object (e.g. unit testing, further processing, etc).
-    pub fn build_vertices(&self) -> Result<Vec<Vertex>, ShapeCreationError> {
+    fn build_unindexed_vertices(&self) -> Result<Vec<Vertex>, ShapeCreationError> {
         // Compute the normal transformation matrix.
-        let normal_matrix = Matrix3::<f32>::from_cols(
-            self.matrix.x.truncate(),
-            self.matrix.y.truncate(),
-            self.matrix.z.truncate(),
-        )
-        .invert()
-        .unwrap_or_else(Matrix3::<f32>::identity)
-        .transpose();
+        let normal_matrix = self.options.normal_matrix();
 
         // Build the vertices.
         let num_axes = 3;
@@ -169,7 +79,8 @@ impl AxesBuilder {
                 normal[axis] = 1.0;
                 let position = (normal * (vert as f32)).extend(1.0);
                 vertices.push(Vertex {
-                    position: Point3::<f32>::from_homogeneous(self.matrix * position).into(),
+                    position: Point3::<f32>::from_homogeneous(self.options.matrix * position)
+                        .into(),
                     normal: (normal_matrix * normal).normalize().into(),
                     texcoord: [vert as f32, axis as f32],
                 });
@@ -178,6 +89,10 @@ impl AxesBuilder {
 
         Ok(vertices)
     }
+
+    fn num_unindexed_vertices(&self) -> usize {
+        3 * 2
+    }
 }
 
 #[test]
@@ -270,3 +185,26 @@ pub fn ensure_axes_uvs_are_in_correct_range() {
     assert!(min == Vector2::<f32>::zero());
     assert!(max == Vector2::<f32>::new(1.0, 2.0));
 }
+
+#[test]
+pub fn ensure_axes_colors_match_axis_numbers() {
+    let (red, green, blue) = (
+        [1.0, 0.0, 0.0, 1.0],
+        [0.0, 1.0, 0.0, 1.0],
+        [0.0, 0.0, 1.0, 1.0],
+    );
+    let vertices = AxesBuilder::new()
+        .with_axis_colors(red, green, blue)
+        .build_vertices_as::<crate::vertex::ColorVertex>()
+        .expect("Failed to build vertices");
+    for vertex in &vertices {
+        let axis = Vector3::<f32>::from(vertex.normal);
+        let expected = Vector3::<f32>::new(vertex.color[0], vertex.color[1], vertex.color[2]);
+        assert_eq!(axis, expected);
+        assert_eq!(vertex.color[3], 1.0);
+        assert_eq!(
+            vertex.color,
+            [red, green, blue][vertex.texcoord[1] as usize]
+        );
+    }
+}
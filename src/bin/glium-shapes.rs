@@ -0,0 +1,396 @@
+//! A command-line tool that generates shapes and writes them to mesh files,
+//! without requiring an OpenGL context.
+//!
+//! Run `glium-shapes --help` for usage.
+
+extern crate glium_shapes;
+
+use glium_shapes::axes::AxesBuilder;
+use glium_shapes::cuboid::CuboidBuilder;
+use glium_shapes::export::gltf::GltfWriter;
+use glium_shapes::export::obj::write_obj;
+use glium_shapes::export::ply::{PlyFormat, PlyWriter};
+use glium_shapes::export::stl::{write_stl_ascii, write_stl_binary};
+use glium_shapes::quad::QuadBuilder;
+use glium_shapes::shape::ShapeBuilder;
+use glium_shapes::sphere::SphereBuilder;
+
+use std::path::{Path, PathBuf};
+
+const USAGE: &str = "\
+Generate a shape and write it to a mesh file.
+
+USAGE:
+    glium-shapes <SHAPE> [OPTIONS] --output <FILE>
+
+SHAPES:
+    axes, cuboid, quad, sphere
+
+OPTIONS:
+    -o, --output <FILE>          The file to write the shape to
+    -f, --format <FORMAT>        The format of the file (obj, stl, stl-ascii, gltf, glb,
+                                 ply, ply-ascii), which is otherwise inferred from the
+                                 file extension
+    -n, --name <NAME>            The name of the shape within the file
+        --divisions <U> <V>      The number of divisions of a sphere
+        --indexed                Share vertices between primitives
+        --scale <X> <Y> <Z>      Scale the shape
+        --translate <X> <Y> <Z>  Translate the shape
+        --rotate-x <DEGREES>     Rotate the shape about the x-axis
+        --rotate-y <DEGREES>     Rotate the shape about the y-axis
+        --rotate-z <DEGREES>     Rotate the shape about the z-axis
+    -h, --help                   Print this message
+
+Transformations are applied in the order in which they are given.";
+
+/// The kinds of shape that can be generated.
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Kind {
+    Axes,
+    Cuboid,
+    Quad,
+    Sphere,
+}
+
+/// The file formats that shapes can be written to.
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Format {
+    Obj,
+    StlBinary,
+    StlAscii,
+    Gltf,
+    Glb,
+    PlyBinary,
+    PlyAscii,
+}
+
+impl Format {
+    /// Returns the format with the given name (as passed to `--format`).
+    fn from_name(name: &str) -> Option<Format> {
+        match name.to_lowercase().as_str() {
+            "obj" => Some(Format::Obj),
+            "stl" => Some(Format::StlBinary),
+            "stl-ascii" => Some(Format::StlAscii),
+            "gltf" => Some(Format::Gltf),
+            "glb" => Some(Format::Glb),
+            "ply" => Some(Format::PlyBinary),
+            "ply-ascii" => Some(Format::PlyAscii),
+            _ => None,
+        }
+    }
+
+    /// Returns the (binary, where applicable) format that corresponds to the
+    /// extension of the given path.
+    fn from_path(path: &Path) -> Option<Format> {
+        path.extension()
+            .and_then(|x| x.to_str())
+            .and_then(Format::from_name)
+            .filter(|x| *x != Format::StlAscii && *x != Format::PlyAscii)
+    }
+}
+
+/// A transformation to apply to the shape.
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Transform {
+    Scale(f32, f32, f32),
+    Translate(f32, f32, f32),
+    RotateX(f32),
+    RotateY(f32),
+    RotateZ(f32),
+}
+
+/// The options parsed from the command-line.
+#[derive(Clone, Debug, PartialEq)]
+struct Options {
+    kind: Kind,
+    output: PathBuf,
+    format: Format,
+    name: Option<String>,
+    divisions: Option<(usize, usize)>,
+    indexed: bool,
+    transforms: Vec<Transform>,
+}
+
+/// Parse the given command-line arguments (excluding the program name), returning
+/// `Ok(None)` if the usage message was requested.
+fn parse_args(args: &[String]) -> Result<Option<Options>, String> {
+    let mut kind = None;
+    let mut output = None;
+    let mut format = None;
+    let mut name = None;
+    let mut divisions = None;
+    let mut indexed = false;
+    let mut transforms = Vec::new();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = |count: usize| -> Result<Vec<&String>, String> {
+            let values = args.by_ref().take(count).collect::<Vec<_>>();
+            if values.len() == count {
+                Ok(values)
+            } else {
+                Err(format!("{} expects {} value(s)", arg, count))
+            }
+        };
+        let float = |value: &String| {
+            value
+                .parse::<f32>()
+                .map_err(|_| format!("Invalid number {} for {}", value, arg))
+        };
+
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "-o" | "--output" => output = Some(PathBuf::from(value(1)?[0])),
+            "-f" | "--format" => {
+                let value = value(1)?[0];
+                format = Some(Format::from_name(value).ok_or(format!("Unknown format {}", value))?);
+            }
+            "-n" | "--name" => name = Some(value(1)?[0].clone()),
+            "--divisions" => {
+                let values = value(2)?;
+                let parse = |value: &String| {
+                    value
+                        .parse::<usize>()
+                        .map_err(|_| format!("Invalid number {} for {}", value, arg))
+                };
+                divisions = Some((parse(values[0])?, parse(values[1])?));
+            }
+            "--indexed" => indexed = true,
+            "--scale" | "--translate" => {
+                let values = value(3)?;
+                let (x, y, z) = (float(values[0])?, float(values[1])?, float(values[2])?);
+                transforms.push(if arg == "--scale" {
+                    Transform::Scale(x, y, z)
+                } else {
+                    Transform::Translate(x, y, z)
+                });
+            }
+            "--rotate-x" => transforms.push(Transform::RotateX(float(value(1)?[0])?)),
+            "--rotate-y" => transforms.push(Transform::RotateY(float(value(1)?[0])?)),
+            "--rotate-z" => transforms.push(Transform::RotateZ(float(value(1)?[0])?)),
+            _ if arg.starts_with('-') => return Err(format!("Unknown option {}", arg)),
+            _ if kind.is_some() => return Err(format!("Unexpected argument {}", arg)),
+            _ => {
+                kind = Some(match arg.to_lowercase().as_str() {
+                    "axes" => Kind::Axes,
+                    "cuboid" => Kind::Cuboid,
+                    "quad" => Kind::Quad,
+                    "sphere" => Kind::Sphere,
+                    _ => return Err(format!("Unknown shape {}", arg)),
+                })
+            }
+        }
+    }
+
+    let kind = kind.ok_or("No shape was given")?;
+    let output = output.ok_or("No output file was given")?;
+    let format = match format {
+        Some(format) => format,
+        None => Format::from_path(&output).ok_or(format!(
+            "Unable to infer the format of {}, use --format",
+            output.display()
+        ))?,
+    };
+    // The buffer of a .gltf file is written alongside it, with a .bin extension.
+    let is_bin = output
+        .extension()
+        .and_then(|x| x.to_str())
+        .is_some_and(|x| x.eq_ignore_ascii_case("bin"));
+    if format == Format::Gltf && is_bin {
+        return Err(format!(
+            "The buffer of {} would overwrite it, use a .gltf extension",
+            output.display()
+        ));
+    }
+    if divisions.is_some() && kind != Kind::Sphere {
+        return Err("--divisions is only supported by sphere".to_string());
+    }
+
+    Ok(Some(Options {
+        kind,
+        output,
+        format,
+        name,
+        divisions,
+        indexed,
+        transforms,
+    }))
+}
+
+/// Apply the options shared by every shape to a builder, including each of the
+/// transformations, in order.
+fn configure<B: ShapeBuilder>(builder: B, options: &Options) -> B {
+    let builder = if options.indexed {
+        builder.indexed()
+    } else {
+        builder
+    };
+    options
+        .transforms
+        .iter()
+        .fold(builder, |builder, transform| match *transform {
+            Transform::Scale(x, y, z) => builder.scale(x, y, z),
+            Transform::Translate(x, y, z) => builder.translate(x, y, z),
+            Transform::RotateX(x) => builder.rotate_x(x.to_radians()),
+            Transform::RotateY(y) => builder.rotate_y(y.to_radians()),
+            Transform::RotateZ(z) => builder.rotate_z(z.to_radians()),
+        })
+}
+
+/// Build the shape using the given (configured) builder, and write it to the
+/// output file in the requested format. The shape is exported to memory first,
+/// such that no file is created when the export fails.
+fn write<B: ShapeBuilder>(builder: B, options: &Options) -> Result<(), String> {
+    let vertices = builder.build_vertices().map_err(|x| x.to_string())?;
+    let indices = builder.build_indices().map_err(|x| x.to_string())?;
+    let (name, indices, primitives) = (
+        options.name.as_deref(),
+        Some(&indices[..]),
+        builder.primitives(),
+    );
+
+    let mut data = Vec::new();
+    let mut bin = None;
+    let result = match options.format {
+        Format::Obj => write_obj(&mut data, name, &vertices, indices, primitives),
+        Format::StlBinary => write_stl_binary(&mut data, name, &vertices, indices, primitives),
+        Format::StlAscii => write_stl_ascii(&mut data, name, &vertices, indices, primitives),
+        Format::PlyBinary => PlyWriter::new(PlyFormat::BinaryLittleEndian)
+            .write(&mut data, &vertices, indices, primitives),
+        Format::PlyAscii => {
+            PlyWriter::new(PlyFormat::Ascii).write(&mut data, &vertices, indices, primitives)
+        }
+        Format::Glb | Format::Gltf => {
+            let mut writer = GltfWriter::new();
+            writer
+                .add_shape(name, &vertices, indices, primitives)
+                .and_then(|_| {
+                    if options.format == Format::Glb {
+                        writer.write_glb(&mut data)
+                    } else {
+                        // The buffer is written alongside the .gltf file.
+                        let path = options.output.with_extension("bin");
+                        let uri = path.file_name().and_then(|x| x.to_str()).unwrap_or("");
+                        let mut buffer = Vec::new();
+                        writer.write_gltf(&mut data, &mut buffer, uri)?;
+                        bin = Some((path, buffer));
+                        Ok(())
+                    }
+                })
+        }
+    };
+    result.map_err(|x| format!("Failed to write {}: {}", options.output.display(), x))?;
+
+    let save = |path: &Path, data: &[u8]| {
+        std::fs::write(path, data).map_err(|x| format!("Failed to write {}: {}", path.display(), x))
+    };
+    if let Some((path, buffer)) = bin {
+        save(&path, &buffer)?;
+    }
+    save(&options.output, &data)
+}
+
+/// Generate the shape described by the given options, and write it to disk.
+fn run(options: &Options) -> Result<(), String> {
+    match options.kind {
+        Kind::Axes => write(configure(AxesBuilder::new(), options), options),
+        Kind::Cuboid => write(configure(CuboidBuilder::new(), options), options),
+        Kind::Quad => write(configure(QuadBuilder::new(), options), options),
+        Kind::Sphere => {
+            let builder = match options.divisions {
+                Some((u, v)) => SphereBuilder::new().with_divisions(u, v),
+                None => SphereBuilder::new(),
+            };
+            write(configure(builder, options), options)
+        }
+    }
+}
+
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<String>>();
+    let result = parse_args(&args).and_then(|options| match options {
+        Some(options) => run(&options),
+        None => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+    });
+    if let Err(error) = result {
+        eprintln!("error: {}", error);
+        eprintln!("Run glium-shapes --help for usage.");
+        std::process::exit(1);
+    }
+}
+
+#[cfg(test)]
+fn args(text: &str) -> Vec<String> {
+    text.split_whitespace().map(|x| x.to_string()).collect()
+}
+
+#[test]
+pub fn ensure_args_are_parsed_in_order() {
+    let options = parse_args(&args(
+        "sphere --divisions 8 4 --scale 2 2 2 --rotate-x 90 --translate 0 1 0 -o out.glb --indexed",
+    ))
+    .expect("Failed to parse args")
+    .expect("Missing options");
+    assert_eq!(options.kind, Kind::Sphere);
+    assert_eq!(options.format, Format::Glb);
+    assert_eq!(options.divisions, Some((8, 4)));
+    assert!(options.indexed);
+    assert_eq!(
+        options.transforms,
+        vec![
+            Transform::Scale(2.0, 2.0, 2.0),
+            Transform::RotateX(90.0),
+            Transform::Translate(0.0, 1.0, 0.0),
+        ]
+    );
+}
+
+#[test]
+pub fn ensure_invalid_args_are_rejected() {
+    assert!(parse_args(&args("torus -o out.obj")).is_err());
+    assert!(parse_args(&args("cuboid -o out.txt")).is_err());
+    assert!(parse_args(&args("cuboid --divisions 8 4 -o out.obj")).is_err());
+    assert!(parse_args(&args("cuboid --scale 1 2 -o out.obj")).is_err());
+    assert!(parse_args(&args("cuboid --format fbx -o out.obj")).is_err());
+    assert!(parse_args(&args("cuboid")).is_err());
+    assert!(parse_args(&args("cuboid --format gltf -o out.bin")).is_err());
+    assert!(parse_args(&args("cuboid --format gltf -o out.BIN")).is_err());
+    assert!(parse_args(&args("cuboid --format glb -o out.bin")).is_ok());
+    assert_eq!(parse_args(&args("cuboid --help")), Ok(None));
+}
+
+#[test]
+pub fn ensure_shapes_are_written_headless() {
+    let directory = std::env::temp_dir().join(format!("glium-shapes-{}", std::process::id()));
+    std::fs::create_dir_all(&directory).expect("Failed to create directory");
+    for (kind, extension) in &[
+        ("sphere", "obj"),
+        ("cuboid", "stl"),
+        ("quad", "gltf"),
+        ("axes", "ply"),
+    ] {
+        let output = directory.join(format!("{}.{}", kind, extension));
+        let options = parse_args(&args(&format!(
+            "{} --indexed -o {}",
+            kind,
+            output.display()
+        )))
+        .expect("Failed to parse args")
+        .expect("Missing options");
+        run(&options).expect("Failed to write shape");
+        assert!(std::fs::metadata(&output).expect("Missing output").len() > 0);
+    }
+    assert!(directory.join("quad.bin").exists());
+    let options = parse_args(&args(&format!(
+        "axes -o {}",
+        directory.join("axes.stl").display()
+    )))
+    .expect("Failed to parse args")
+    .expect("Missing options");
+    assert!(run(&options).is_err());
+    assert!(!directory.join("axes.stl").exists());
+    std::fs::remove_dir_all(&directory).expect("Failed to remove directory");
+}
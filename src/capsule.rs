@@ -0,0 +1,437 @@
+//! A module for constructing capsule shapes.
+
+extern crate cgmath;
+#[cfg(feature = "glium")]
+extern crate glium;
+
+use self::cgmath::*;
+use crate::errors::ShapeCreationError;
+use crate::index;
+#[cfg(feature = "glium")]
+use crate::mesh::Mesh;
+use crate::shading::Shading;
+use crate::shape::{ShapeBuilder, ShapeOptions};
+use crate::sphere::LatLong;
+use crate::vertex::Vertex;
+
+use std::f32;
+
+/// A polygonal `Capsule` object.
+///
+/// This object is constructed using a `CapsuleBuilder` object.
+#[cfg(feature = "glium")]
+pub type Capsule = Mesh;
+
+/// Responsible for building and returning a `Capsule` object.
+///
+/// A capsule is a cylinder whose ends are closed with hemispheres. By default,
+/// the capsule has a radius of 0.5 and a cylinder length of 1 (giving a total
+/// height of 2), with its centre-of-mass located at the origin and its axis
+/// aligned to the y-axis. This can be overriden using the transformation
+/// methods on this object.
+///
+/// The resultant geometry is constructed to suit OpenGL defaults - assuming
+/// a right-handed coordinate system, front-facing polygons are defined in
+/// counter-clock-wise order. By default, vertex normals point in the direction
+/// of their respective face (such that the shape appears faceted when lit),
+/// although smooth normals can be requested instead. Vertex
+/// texture coordinates wrap around the capsule in the u direction, and run
+/// from the top (0) to the bottom (1) of the capsule in the v direction,
+/// proportionally to the distance travelled along its surface. The hemispheres
+/// and the cylinder share their boundary vertices, such that the sections join
+/// without seams.
+pub struct CapsuleBuilder {
+    options: ShapeOptions,
+    radius: f32,
+    length: f32,
+    radial_divisions: usize,
+    hemisphere_divisions: usize,
+    body_divisions: usize,
+}
+
+impl Default for CapsuleBuilder {
+    fn default() -> Self {
+        CapsuleBuilder {
+            options: ShapeOptions::default(),
+            radius: 0.5,
+            length: 1.0,
+            radial_divisions: 24,
+            hemisphere_divisions: 6,
+            body_divisions: 1,
+        }
+    }
+}
+
+impl CapsuleBuilder {
+    /// Create a new `CapsuleBuilder` object.
+    pub fn new() -> CapsuleBuilder {
+        Default::default()
+    }
+
+    /// Specify the radius of the capsule. By default, the radius is 0.5.
+    pub fn with_radius(mut self, radius: f32) -> Self {
+        self.radius = radius;
+        self
+    }
+
+    /// Specify the length of the cylindrical section of the capsule (e.g.
+    /// excluding the hemispheres). By default, the length is 1. A length of 0
+    /// produces a sphere, and negative lengths are treated as 0.
+    pub fn with_length(mut self, length: f32) -> Self {
+        self.length = length;
+        self
+    }
+
+    /// Specify the number of divisions to make around the circumference of the
+    /// capsule (radial), from the pole to the equator of each hemisphere
+    /// (hemisphere), and along the cylindrical section (body). By default, the
+    /// builder will use 24 radial, 6 hemisphere, and 1 body division.
+    pub fn with_divisions(mut self, radial: usize, hemisphere: usize, body: usize) -> Self {
+        self.radial_divisions = radial;
+        self.hemisphere_divisions = hemisphere;
+        self.body_divisions = body;
+        self
+    }
+
+    /// Specify how vertex normals are generated. By default, the builder will
+    /// use `Shading::Faceted`.
+    pub fn with_shading(mut self, shading: Shading) -> Self {
+        self.options.shading = shading;
+        self
+    }
+
+    /// Returns the total number of vertices in each hemisphere.
+    pub fn num_vertices_per_hemisphere(&self) -> usize {
+        // Each hemisphere consists of a row of triangles at the pole, followed
+        // by rows of quads.
+        (3 + (self.hemisphere_divisions - 1) * 6) * self.radial_divisions
+    }
+
+    /// Returns the total number of vertices in the cylindrical section.
+    pub fn num_vertices_per_body(&self) -> usize {
+        if self.length > 0.0 {
+            6 * self.radial_divisions * self.body_divisions
+        } else {
+            0
+        }
+    }
+}
+
+impl ShapeBuilder for CapsuleBuilder {
+    fn options(&self) -> &ShapeOptions {
+        &self.options
+    }
+
+    fn options_mut(&mut self) -> &mut ShapeOptions {
+        &mut self.options
+    }
+
+    fn primitives(&self) -> index::PrimitiveType {
+        index::PrimitiveType::TrianglesList
+    }
+
+    fn build_unindexed_vertices(&self) -> Result<Vec<Vertex>, ShapeCreationError> {
+        // Ensure there are enough divisions to produce valid capsule geometry.
+        if self.radial_divisions < 3 {
+            return Err(ShapeCreationError::NotEnoughDivisionsInU);
+        }
+
+        if self.hemisphere_divisions < 1 || self.body_divisions < 1 {
+            return Err(ShapeCreationError::NotEnoughDivisionsInV);
+        }
+
+        if self.radius <= 0.0 {
+            return Err(ShapeCreationError::InvalidRadius);
+        }
+
+        // Build lookup tables. The hemispheres are built from the top and bottom
+        // halves of a latitude/longitude sphere, and the rings of the cylindrical
+        // section are offset copies of its equator.
+        let lat_long = LatLong::new(self.radial_divisions, self.hemisphere_divisions * 2);
+        let half_length = self.length.max(0.0) * 0.5;
+        let equator = self.hemisphere_divisions;
+
+        let y_tab = (0..=self.body_divisions)
+            .map(|x| half_length * (1.0 - 2.0 * (x as f32) / (self.body_divisions as f32)))
+            .collect::<Vec<f32>>();
+
+        // Texture v coordinates are proportional to the distance along the profile
+        // of the capsule.
+        let hemisphere_length = self.radius * f32::consts::FRAC_PI_2;
+        let profile_length = 2.0 * (hemisphere_length + half_length);
+        let hemisphere_v = |v: usize, bottom: bool| {
+            let arc = hemisphere_length * v as f32 / self.hemisphere_divisions as f32;
+            if bottom {
+                (arc + 2.0 * half_length) / profile_length
+            } else {
+                arc / profile_length
+            }
+        };
+        let body_v = |v: usize| (hemisphere_length + (half_length - y_tab[v])) / profile_length;
+
+        // Compute the normal transformation matrix.
+        let normal_matrix = self.options.normal_matrix();
+
+        // Build vertex array.
+        let total_num_verts = self.num_unindexed_vertices();
+        let mut vertices = Vec::<Vertex>::with_capacity(total_num_verts);
+
+        let mut emit = |vpos: Vector3<f32>, normal: Vector3<f32>, texcoord: [f32; 2]| {
+            let pos = self.options.matrix * vpos.extend(1.0);
+            vertices.push(Vertex {
+                position: Point3::<f32>::from_homogeneous(pos).into(),
+                normal: (normal_matrix * normal).normalize().into(),
+                texcoord,
+            });
+        };
+
+        // Emit the top hemisphere.
+        let top_offset = Vector3::<f32>::new(0.0, y_tab[0], 0.0);
+        for v in 0..equator {
+            lat_long.build_row(v, |vpos, normal, (u, v)| {
+                let normal = match self.options.shading {
+                    Shading::Faceted => normal,
+                    Shading::Smooth => vpos,
+                };
+                emit(
+                    vpos * self.radius + top_offset,
+                    normal,
+                    [
+                        u as f32 / self.radial_divisions as f32,
+                        hemisphere_v(v, false),
+                    ],
+                );
+            });
+        }
+
+        // Emit the cylindrical section.
+        if half_length > 0.0 {
+            let indices = [0, 1, 2, 2, 1, 3];
+            let ring = |u: usize, v: usize| {
+                lat_long.point(u, equator) * self.radius + Vector3::<f32>::new(0.0, y_tab[v], 0.0)
+            };
+
+            for v in 0..self.body_divisions {
+                for u in 0..self.radial_divisions {
+                    // Compute slice vertices
+                    let verts = [
+                        ring(u + 1, v),
+                        ring(u + 1, v + 1),
+                        ring(u, v),
+                        ring(u, v + 1),
+                    ];
+
+                    let lut_coords = [(u + 1, v), (u + 1, v + 1), (u, v), (u, v + 1)];
+
+                    // Compute face normal
+                    let normal = (verts[1] - verts[0]).cross(verts[2] - verts[0]).normalize();
+
+                    // Emit vertices.
+                    for index in &indices {
+                        let (u, v) = lut_coords[*index];
+                        let normal = match self.options.shading {
+                            Shading::Faceted => normal,
+                            Shading::Smooth => lat_long.point(u, equator),
+                        };
+                        emit(
+                            verts[*index],
+                            normal,
+                            [u as f32 / self.radial_divisions as f32, body_v(v)],
+                        );
+                    }
+                }
+            }
+        }
+
+        // Emit the bottom hemisphere.
+        let bottom_offset = Vector3::<f32>::new(0.0, y_tab[self.body_divisions], 0.0);
+        for v in equator..equator * 2 {
+            lat_long.build_row(v, |vpos, normal, (u, v)| {
+                let normal = match self.options.shading {
+                    Shading::Faceted => normal,
+                    Shading::Smooth => vpos,
+                };
+                emit(
+                    vpos * self.radius + bottom_offset,
+                    normal,
+                    [
+                        u as f32 / self.radial_divisions as f32,
+                        hemisphere_v(v, true),
+                    ],
+                );
+            });
+        }
+
+        assert!(vertices.len() == total_num_verts);
+        Ok(vertices)
+    }
+
+    fn num_unindexed_vertices(&self) -> usize {
+        self.num_vertices_per_hemisphere() * 2 + self.num_vertices_per_body()
+    }
+}
+
+#[test]
+pub fn ensure_default_capsule_has_correct_dimensions() {
+    let vertices = CapsuleBuilder::new()
+        .build_vertices()
+        .expect("Failed to build vertices");
+    for vertex in &vertices {
+        let pos = Vector3::<f32>::from(vertex.position);
+        let axis = Vector3::<f32>::new(0.0, pos.y.clamp(-0.5, 0.5), 0.0);
+        assert_ulps_eq!((pos - axis).magnitude(), 0.5, epsilon = 0.0001);
+        assert!(pos.y.abs() <= 1.0);
+    }
+}
+
+#[test]
+pub fn ensure_default_capsule_has_centroid_at_origin() {
+    let vertices = CapsuleBuilder::new()
+        .build_vertices()
+        .expect("Failed to build vertices");
+    let mut sum = Vector3::<f32>::zero();
+    for vertex in &vertices {
+        sum += Vector3::<f32>::from(vertex.position);
+    }
+    assert_ulps_eq!(sum, Vector3::<f32>::zero(), epsilon = 0.0001);
+}
+
+#[test]
+pub fn ensure_default_capsule_has_outward_facing_normals() {
+    let vertices = CapsuleBuilder::new()
+        .scale(2.0, 2.0, 2.0)
+        .build_vertices()
+        .expect("Failed to build vertices");
+    for vertex in &vertices {
+        let position = Vector3::<f32>::from(vertex.position);
+        let normal = Vector3::<f32>::from(vertex.normal);
+        let outside = position + normal;
+        assert!(outside.x.abs() >= position.x.abs());
+        assert!(outside.y.abs() >= position.y.abs());
+        assert!(outside.z.abs() >= position.z.abs());
+    }
+}
+
+#[test]
+pub fn ensure_default_capsule_has_uvs_in_unit_range() {
+    let vertices = CapsuleBuilder::new()
+        .with_divisions(4, 2, 2)
+        .build_vertices()
+        .expect("Failed to build vertices");
+    for vertex in &vertices {
+        assert!(vertex.texcoord[0] >= 0.0);
+        assert!(vertex.texcoord[1] >= 0.0);
+        assert!(vertex.texcoord[0] <= 1.0);
+        assert!(vertex.texcoord[1] <= 1.0 + 1e-6);
+    }
+}
+
+#[test]
+pub fn ensure_default_capsule_has_ccw_triangles() {
+    let vertices = CapsuleBuilder::new()
+        .build_vertices()
+        .expect("Failed to build vertices");
+    for chunk in vertices.chunks(3) {
+        let v0 = Vector3::<f32>::from(chunk[0].position);
+        let v1 = Vector3::<f32>::from(chunk[1].position);
+        let v2 = Vector3::<f32>::from(chunk[2].position);
+        let eyepos = v0 + Vector3::<f32>::from(chunk[0].normal);
+        let e0 = v1 - v0;
+        let e1 = v2 - v0;
+        let n = e0.cross(e1);
+        assert!(n.dot(v0 - eyepos) <= 0.0);
+        assert!(n.dot(v1 - eyepos) <= 0.0);
+        assert!(n.dot(v2 - eyepos) <= 0.0);
+    }
+}
+
+#[test]
+pub fn ensure_default_capsule_has_faceted_normals() {
+    let vertices = CapsuleBuilder::new()
+        .build_vertices()
+        .expect("Failed to build vertices");
+
+    for chunk in vertices.chunks(3) {
+        let v0 = Vector3::<f32>::from(chunk[0].position);
+        let v1 = Vector3::<f32>::from(chunk[1].position);
+        let v2 = Vector3::<f32>::from(chunk[2].position);
+        let n0 = Vector3::<f32>::from(chunk[0].normal);
+        let n1 = Vector3::<f32>::from(chunk[1].normal);
+        let n2 = Vector3::<f32>::from(chunk[2].normal);
+        let e0 = v1 - v0;
+        let e1 = v2 - v0;
+        let n = e0.cross(e1).normalize();
+        assert_ulps_eq!(n, n0, epsilon = 0.0001);
+        assert_ulps_eq!(n, n1, epsilon = 0.0001);
+        assert_ulps_eq!(n, n2, epsilon = 0.0001);
+    }
+}
+
+#[test]
+pub fn ensure_capsule_sections_join_without_seams() {
+    let builder = CapsuleBuilder::new().with_divisions(8, 3, 2);
+    let vertices = builder.build_vertices().expect("Failed to build vertices");
+
+    // Every vertex on the boundary between the hemispheres and the cylindrical
+    // section must be shared exactly (position and texture coordinate) by both.
+    let hemisphere = builder.num_vertices_per_hemisphere();
+    let body = builder.num_vertices_per_body();
+    let sections = [
+        &vertices[..hemisphere],
+        &vertices[hemisphere..hemisphere + body],
+        &vertices[hemisphere + body..],
+    ];
+    for (a, b) in &[(0, 1), (2, 1)] {
+        let boundary = sections[*a]
+            .iter()
+            .filter(|x| abs_diff_eq!(x.position[1].abs(), 0.5, epsilon = 1e-6))
+            .collect::<Vec<_>>();
+        assert!(!boundary.is_empty());
+        for vertex in boundary {
+            assert!(sections[*b]
+                .iter()
+                .any(|x| x.position == vertex.position && x.texcoord == vertex.texcoord));
+        }
+    }
+}
+
+#[test]
+pub fn ensure_capsule_with_invalid_parameters_fails() {
+    match CapsuleBuilder::new()
+        .with_divisions(2, 1, 1)
+        .build_vertices()
+    {
+        Err(ShapeCreationError::NotEnoughDivisionsInU) => {}
+        _ => panic!("Expected NotEnoughDivisionsInU"),
+    }
+    match CapsuleBuilder::new()
+        .with_divisions(3, 0, 1)
+        .build_vertices()
+    {
+        Err(ShapeCreationError::NotEnoughDivisionsInV) => {}
+        _ => panic!("Expected NotEnoughDivisionsInV"),
+    }
+    match CapsuleBuilder::new().with_radius(0.0).build_vertices() {
+        Err(ShapeCreationError::InvalidRadius) => {}
+        _ => panic!("Expected InvalidRadius"),
+    }
+}
+
+#[test]
+pub fn ensure_smooth_capsule_has_normals_pointing_away_from_axis() {
+    let vertices = CapsuleBuilder::new()
+        .with_shading(Shading::Smooth)
+        .build_vertices()
+        .expect("Failed to build vertices");
+    for vertex in &vertices {
+        // The closest point on the axis segment between the hemisphere centres.
+        let position = Vector3::<f32>::from(vertex.position);
+        let axis = Vector3::<f32>::new(0.0, position.y.clamp(-0.5, 0.5), 0.0);
+        let expected = (position - axis).normalize();
+        assert_ulps_eq!(
+            Vector3::<f32>::from(vertex.normal),
+            expected,
+            epsilon = 0.0001
+        );
+    }
+}
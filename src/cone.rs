@@ -0,0 +1,454 @@
+//! A module for constructing cone and truncated-cone (frustum) shapes.
+
+extern crate cgmath;
+#[cfg(feature = "glium")]
+extern crate glium;
+
+use self::cgmath::*;
+use crate::errors::ShapeCreationError;
+use crate::index;
+#[cfg(feature = "glium")]
+use crate::mesh::Mesh;
+use crate::shading::Shading;
+use crate::shape::{ShapeBuilder, ShapeOptions};
+use crate::vertex::Vertex;
+
+use std::f32;
+
+/// A polygonal `Cone` object.
+///
+/// This object is constructed using a `ConeBuilder` object.
+#[cfg(feature = "glium")]
+pub type Cone = Mesh;
+
+/// Responsible for building and returning a `Cone` object.
+///
+/// By default, the cone has a base radius of 1 and a height of 2, with its
+/// axis aligned to the y-axis, its base located at y = -1, and its apex
+/// located at y = 1. The base of the cone is closed with a cap. Specifying a
+/// non-zero top radius produces a truncated cone (frustum), whose top may
+/// also be capped. This can be overriden using the transformation methods
+/// on this object.
+///
+/// The resultant geometry is constructed to suit OpenGL defaults - assuming
+/// a right-handed coordinate system, front-facing polygons are defined in
+/// counter-clock-wise order. By default, vertex normals point in the direction
+/// of their respective face (such that the shape appears faceted when lit),
+/// although smooth normals can be requested instead. Vertex
+/// texture coordinates unwrap the side of the cone such that u runs around
+/// its circumference and v runs from its top (0) to its base (1), and define
+/// a planar-projection on each cap.
+pub struct ConeBuilder {
+    options: ShapeOptions,
+    radial_divisions: usize,
+    base_radius: f32,
+    top_radius: f32,
+    top_cap: bool,
+    bottom_cap: bool,
+}
+
+impl Default for ConeBuilder {
+    fn default() -> Self {
+        ConeBuilder {
+            options: ShapeOptions::default(),
+            radial_divisions: 24,
+            base_radius: 1.0,
+            top_radius: 0.0,
+            top_cap: true,
+            bottom_cap: true,
+        }
+    }
+}
+
+impl ConeBuilder {
+    /// Create a new `ConeBuilder` object.
+    pub fn new() -> ConeBuilder {
+        Default::default()
+    }
+
+    /// Specify the number of divisions to make around the circumference of
+    /// the cone. By default, the builder will use 24 divisions.
+    pub fn with_divisions(mut self, radial: usize) -> Self {
+        self.radial_divisions = radial;
+        self
+    }
+
+    /// Specify the radius of the base of the cone. By default, the base
+    /// radius is 1.
+    pub fn with_base_radius(mut self, radius: f32) -> Self {
+        self.base_radius = radius;
+        self
+    }
+
+    /// Specify the radius of the top of the cone. A radius of 0 (the default)
+    /// produces a cone with a pointed apex, and a non-zero radius produces a
+    /// truncated cone.
+    pub fn with_top_radius(mut self, radius: f32) -> Self {
+        self.top_radius = radius;
+        self
+    }
+
+    /// Specify whether the top and bottom (base) ends of the cone are closed
+    /// with a cap. By default, both ends are capped. The top cap is only
+    /// generated for truncated cones.
+    pub fn with_caps(mut self, top: bool, bottom: bool) -> Self {
+        self.top_cap = top;
+        self.bottom_cap = bottom;
+        self
+    }
+
+    /// Specify how vertex normals are generated. By default, the builder will
+    /// use `Shading::Faceted`. The caps are always flat.
+    pub fn with_shading(mut self, shading: Shading) -> Self {
+        self.options.shading = shading;
+        self
+    }
+
+    fn has_top_cap(&self) -> bool {
+        self.top_cap && self.top_radius > 0.0
+    }
+
+    fn has_bottom_cap(&self) -> bool {
+        self.bottom_cap && self.base_radius > 0.0
+    }
+
+    /// Returns the number of caps in the resultant cone geometry. The resultant value
+    /// will depend on the caps and radii specified on the builder.
+    pub fn num_caps(&self) -> usize {
+        self.has_top_cap() as usize + self.has_bottom_cap() as usize
+    }
+
+    /// Returns the number of vertices generated for each cap face. The current implementation
+    /// will always return 3.
+    pub fn num_vertices_per_cap_face(&self) -> usize {
+        3
+    }
+
+    /// Returns the total number of vertices in each cap.
+    pub fn num_vertices_per_cap(&self) -> usize {
+        self.num_vertices_per_cap_face() * self.radial_divisions
+    }
+
+    /// Returns the total number of vertices in each side face (e.g. excluding caps). This
+    /// will return 3 when the cone comes to a point, or 6 for a truncated cone.
+    pub fn num_vertices_per_side_face(&self) -> usize {
+        if self.top_radius == 0.0 || self.base_radius == 0.0 {
+            3
+        } else {
+            6
+        }
+    }
+
+    /// Returns the total number of vertices in the side of the cone.
+    pub fn num_vertices_per_side(&self) -> usize {
+        self.num_vertices_per_side_face() * self.radial_divisions
+    }
+}
+
+impl ShapeBuilder for ConeBuilder {
+    fn options(&self) -> &ShapeOptions {
+        &self.options
+    }
+
+    fn options_mut(&mut self) -> &mut ShapeOptions {
+        &mut self.options
+    }
+
+    fn primitives(&self) -> index::PrimitiveType {
+        index::PrimitiveType::TrianglesList
+    }
+
+    fn build_unindexed_vertices(&self) -> Result<Vec<Vertex>, ShapeCreationError> {
+        // Ensure there are enough divisions around the cone, and that the
+        // radii produce valid geometry.
+        if self.radial_divisions < 3 {
+            return Err(ShapeCreationError::NotEnoughDivisionsInU);
+        }
+
+        let radii = [self.base_radius, self.top_radius];
+        if radii.iter().any(|x| x.is_nan() || *x < 0.0) || radii.iter().all(|x| *x == 0.0) {
+            return Err(ShapeCreationError::InvalidRadius);
+        }
+
+        // Build lookup tables.
+        let u_angle = 2.0 * f32::consts::PI / self.radial_divisions as f32;
+
+        fn sin_cos(val: f32) -> [f32; 2] {
+            [val.sin(), val.cos()]
+        }
+
+        let u_tab = (0..=self.radial_divisions)
+            .map(|x| sin_cos(((x % self.radial_divisions) as f32) * u_angle))
+            .collect::<Vec<[f32; 2]>>();
+
+        let r_tab = [self.top_radius, self.base_radius];
+        let y_tab = [1.0, -1.0];
+
+        let indices = [0, 1, 2, 2, 1, 3];
+
+        // Compute the normal transformation matrix.
+        let normal_matrix = self.options.normal_matrix();
+
+        // Build vertex array.
+        let total_num_verts = self.num_unindexed_vertices();
+        let mut vertices = Vec::<Vertex>::with_capacity(total_num_verts);
+
+        let mut emit = |vpos: Vector3<f32>, normal: Vector3<f32>, texcoord: [f32; 2]| {
+            let pos = self.options.matrix * vpos.extend(1.0);
+            vertices.push(Vertex {
+                position: Point3::<f32>::from_homogeneous(pos).into(),
+                normal: (normal_matrix * normal).normalize().into(),
+                texcoord,
+            });
+        };
+
+        let ring = |u: usize, v: usize| {
+            Vector3::<f32>::new(u_tab[u][1] * r_tab[v], y_tab[v], u_tab[u][0] * r_tab[v])
+        };
+
+        let cap_texcoord = |vpos: &Vector3<f32>, radius: f32| {
+            [vpos.x / radius * 0.5 + 0.5, vpos.z / radius * 0.5 + 0.5]
+        };
+
+        // Emit the top cap.
+        if self.has_top_cap() {
+            let centre = Vector3::<f32>::new(0.0, 1.0, 0.0);
+            let normal = Vector3::<f32>::unit_y();
+            for u in 0..self.radial_divisions {
+                for vpos in &[centre, ring(u + 1, 0), ring(u, 0)] {
+                    emit(*vpos, normal, cap_texcoord(vpos, self.top_radius));
+                }
+            }
+        }
+
+        // Emit the side. When either end of the cone comes to a point, only
+        // the triangle that does not touch the point is emitted (the apex
+        // texture coordinate is centred over the face it belongs to).
+        let (offset, count) = if self.top_radius == 0.0 {
+            (3, 3)
+        } else if self.base_radius == 0.0 {
+            (0, 3)
+        } else {
+            (0, 6)
+        };
+
+        for u in 0..self.radial_divisions {
+            // Compute side vertices
+            let verts = [ring(u + 1, 0), ring(u + 1, 1), ring(u, 0), ring(u, 1)];
+
+            let lut_coords = [(u + 1, 0), (u + 1, 1), (u, 0), (u, 1)];
+
+            // Compute face normal
+            let v0 = &verts[indices[offset]];
+            let v1 = &verts[indices[offset + 1]];
+            let v2 = &verts[indices[offset + 2]];
+            let normal = (v1 - v0).cross(v2 - v0).normalize();
+
+            // Emit vertices.
+            for index in &indices[offset..offset + count] {
+                let (lut_u, lut_v) = lut_coords[*index];
+                let tex_u = if r_tab[lut_v] == 0.0 {
+                    u as f32 + 0.5
+                } else {
+                    lut_u as f32
+                };
+
+                // Smooth normals are perpendicular to the slope of the side. The
+                // apex takes the normal from the centre of the face it belongs to.
+                let normal = match self.options.shading {
+                    Shading::Faceted => normal,
+                    Shading::Smooth => {
+                        let [sin, cos] = if r_tab[lut_v] == 0.0 {
+                            sin_cos(tex_u * u_angle)
+                        } else {
+                            u_tab[lut_u]
+                        };
+                        Vector3::<f32>::new(
+                            2.0 * cos,
+                            self.base_radius - self.top_radius,
+                            2.0 * sin,
+                        )
+                    }
+                };
+                emit(
+                    verts[*index],
+                    normal,
+                    [tex_u / self.radial_divisions as f32, lut_v as f32],
+                );
+            }
+        }
+
+        // Emit the bottom cap.
+        if self.has_bottom_cap() {
+            let centre = Vector3::<f32>::new(0.0, -1.0, 0.0);
+            let normal = -Vector3::<f32>::unit_y();
+            for u in 0..self.radial_divisions {
+                for vpos in &[centre, ring(u, 1), ring(u + 1, 1)] {
+                    emit(*vpos, normal, cap_texcoord(vpos, self.base_radius));
+                }
+            }
+        }
+
+        assert!(vertices.len() == total_num_verts);
+        Ok(vertices)
+    }
+
+    fn num_unindexed_vertices(&self) -> usize {
+        self.num_vertices_per_side() + (self.num_vertices_per_cap() * self.num_caps())
+    }
+}
+
+#[test]
+pub fn ensure_default_cone_has_unit_base_and_apex_at_top() {
+    let vertices = ConeBuilder::new()
+        .build_vertices()
+        .expect("Failed to build vertices");
+    for vertex in &vertices {
+        let pos = Vector3::<f32>::from(vertex.position);
+        let radius = Vector2::<f32>::new(pos.x, pos.z).magnitude();
+        if pos.y > 0.0 {
+            assert!(abs_diff_eq!(pos.y, 1.0));
+            assert!(abs_diff_eq!(radius, 0.0));
+        } else {
+            assert!(abs_diff_eq!(pos.y, -1.0));
+            assert!(radius <= 1.0 + 1e-6);
+        }
+    }
+}
+
+#[test]
+pub fn ensure_default_cone_has_outward_facing_normals() {
+    let vertices = ConeBuilder::new()
+        .scale(2.0, 2.0, 2.0)
+        .build_vertices()
+        .expect("Failed to build vertices");
+    for vertex in &vertices {
+        let position = Vector3::<f32>::from(vertex.position);
+        let normal = Vector3::<f32>::from(vertex.normal);
+        assert!(position.dot(normal) > 0.0);
+    }
+}
+
+#[test]
+pub fn ensure_default_cone_has_uvs_in_unit_range() {
+    let vertices = ConeBuilder::new()
+        .with_divisions(4)
+        .build_vertices()
+        .expect("Failed to build vertices");
+    for vertex in &vertices {
+        assert!(vertex.texcoord[0] >= 0.0);
+        assert!(vertex.texcoord[1] >= 0.0);
+        assert!(vertex.texcoord[0] <= 1.0);
+        assert!(vertex.texcoord[1] <= 1.0);
+    }
+}
+
+#[test]
+pub fn ensure_default_cone_has_ccw_triangles() {
+    let vertices = ConeBuilder::new()
+        .build_vertices()
+        .expect("Failed to build vertices");
+    for chunk in vertices.chunks(3) {
+        let v0 = Vector3::<f32>::from(chunk[0].position);
+        let v1 = Vector3::<f32>::from(chunk[1].position);
+        let v2 = Vector3::<f32>::from(chunk[2].position);
+        let eyepos = v0 + Vector3::<f32>::from(chunk[0].normal);
+        let e0 = v1 - v0;
+        let e1 = v2 - v0;
+        let n = e0.cross(e1);
+        assert!(n.dot(v0 - eyepos) <= 0.0);
+        assert!(n.dot(v1 - eyepos) <= 0.0);
+        assert!(n.dot(v2 - eyepos) <= 0.0);
+    }
+}
+
+#[test]
+pub fn ensure_truncated_cone_has_faceted_normals() {
+    let vertices = ConeBuilder::new()
+        .with_top_radius(0.5)
+        .build_vertices()
+        .expect("Failed to build vertices");
+
+    for chunk in vertices.chunks(3) {
+        let v0 = Vector3::<f32>::from(chunk[0].position);
+        let v1 = Vector3::<f32>::from(chunk[1].position);
+        let v2 = Vector3::<f32>::from(chunk[2].position);
+        let n0 = Vector3::<f32>::from(chunk[0].normal);
+        let n1 = Vector3::<f32>::from(chunk[1].normal);
+        let n2 = Vector3::<f32>::from(chunk[2].normal);
+        let e0 = v1 - v0;
+        let e1 = v2 - v0;
+        let n = e0.cross(e1).normalize();
+        assert_ulps_eq!(n, n0, epsilon = 0.0001);
+        assert_ulps_eq!(n, n1, epsilon = 0.0001);
+        assert_ulps_eq!(n, n2, epsilon = 0.0001);
+    }
+}
+
+#[test]
+pub fn ensure_truncated_cone_has_distinct_radii() {
+    let builder = ConeBuilder::new()
+        .with_divisions(8)
+        .with_base_radius(2.0)
+        .with_top_radius(0.5)
+        .with_caps(true, false);
+    let vertices = builder.build_vertices().expect("Failed to build vertices");
+    assert_eq!(builder.num_caps(), 1);
+    assert_eq!(vertices.len(), 8 * 6 + 8 * 3);
+    for vertex in &vertices {
+        let pos = Vector3::<f32>::from(vertex.position);
+        let radius = Vector2::<f32>::new(pos.x, pos.z).magnitude();
+        if pos.y > 0.0 {
+            assert!(radius <= 0.5 + 1e-6);
+        } else {
+            assert!(abs_diff_eq!(radius, 2.0, epsilon = 1e-6));
+        }
+    }
+}
+
+#[test]
+pub fn ensure_cone_with_invalid_parameters_fails() {
+    match ConeBuilder::new().with_divisions(2).build_vertices() {
+        Err(ShapeCreationError::NotEnoughDivisionsInU) => {}
+        _ => panic!("Expected NotEnoughDivisionsInU"),
+    }
+    match ConeBuilder::new().with_base_radius(0.0).build_vertices() {
+        Err(ShapeCreationError::InvalidRadius) => {}
+        _ => panic!("Expected InvalidRadius"),
+    }
+    match ConeBuilder::new().with_top_radius(-1.0).build_vertices() {
+        Err(ShapeCreationError::InvalidRadius) => {}
+        _ => panic!("Expected InvalidRadius"),
+    }
+    match ConeBuilder::new()
+        .with_base_radius(f32::NAN)
+        .build_vertices()
+    {
+        Err(ShapeCreationError::InvalidRadius) => {}
+        _ => panic!("Expected InvalidRadius"),
+    }
+    match ConeBuilder::new()
+        .with_top_radius(f32::NAN)
+        .build_vertices()
+    {
+        Err(ShapeCreationError::InvalidRadius) => {}
+        _ => panic!("Expected InvalidRadius"),
+    }
+}
+
+#[test]
+pub fn ensure_smooth_cone_has_sloped_side_normals() {
+    let builder = ConeBuilder::new()
+        .with_shading(Shading::Smooth)
+        .with_caps(false, false);
+    let vertices = builder.build_vertices().expect("Failed to build vertices");
+
+    // The side of the default cone rises by 2 as its radius falls by 1.
+    let slope = 1.0 / 5.0f32.sqrt();
+    for vertex in &vertices {
+        let position = Vector3::<f32>::from(vertex.position);
+        let normal = Vector3::<f32>::from(vertex.normal);
+        assert_ulps_eq!(normal.y, slope, epsilon = 0.0001);
+        assert!(position.x * normal.x + position.z * normal.z >= 0.0);
+    }
+}
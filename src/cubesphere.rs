@@ -0,0 +1,439 @@
+//! A module for constructing cube-sphere (spherified cube) shapes.
+
+extern crate cgmath;
+#[cfg(feature = "glium")]
+extern crate glium;
+
+use self::cgmath::*;
+use crate::cuboid::{corner_position, INDEX_LUT, POLY_LUT};
+use crate::errors::ShapeCreationError;
+use crate::index;
+#[cfg(feature = "glium")]
+use crate::mesh::Mesh;
+use crate::shading::Shading;
+use crate::shape::{ShapeBuilder, ShapeOptions};
+use crate::vertex::Vertex;
+
+use std::f32;
+
+/// A polygonal `CubeSphere` object.
+///
+/// This object is constructed using a `CubeSphereBuilder` object.
+#[cfg(feature = "glium")]
+pub type CubeSphere = Mesh;
+
+/// Determines how the grid on each face of the cube is projected onto the sphere.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum CubeSphereProjection {
+    /// Each grid point is projected onto the sphere by normalizing its position.
+    /// This is cheap, but produces cells that are larger at the centre of each
+    /// face than at its corners.
+    #[default]
+    Normalized,
+
+    /// Each grid point is projected onto the sphere using an area-preserving
+    /// mapping, such that every cell covers the same area of the sphere.
+    EqualArea,
+}
+
+/// Determines how texture coordinates are laid out on a cube-sphere.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum CubeSphereTexcoords {
+    /// Each face of the cube is mapped to the full 0 to 1 range, using the same
+    /// orientation as the faces of a `CuboidBuilder`. This is the layout to use
+    /// when each face samples its own texture (or face of a cubemap).
+    #[default]
+    PerFace,
+
+    /// The faces of the cube are packed into a single texture atlas, arranged in
+    /// a grid of three columns and two rows. The -X, +X, and -Y faces occupy the
+    /// bottom row (in that order), and the +Y, -Z, and +Z faces occupy the top row.
+    Atlas,
+}
+
+/// Responsible for building and returning a `CubeSphere` object.
+///
+/// A cube-sphere is built by subdividing each side of a cube into a grid, and
+/// projecting the grid onto a sphere. By default, the cube-sphere is defined as
+/// a unit-sphere (e.g. a radius of 1) with its centre-of-mass located at the
+/// origin. This can be overriden using the transformation methods on this object.
+///
+/// The resultant geometry is constructed to suit OpenGL defaults - assuming
+/// a right-handed coordinate system, front-facing polygons are defined in
+/// counter-clock-wise order. By default, vertex normals point in the direction
+/// of their respective face (such that the shape appears faceted when lit),
+/// although smooth normals can be requested instead. Vertex texture coordinates
+/// define a planar-projection on each face of the cube, either per-face or
+/// packed into a single atlas.
+pub struct CubeSphereBuilder {
+    options: ShapeOptions,
+    divisions: usize,
+    projection: CubeSphereProjection,
+    texcoords: CubeSphereTexcoords,
+}
+
+impl Default for CubeSphereBuilder {
+    fn default() -> Self {
+        CubeSphereBuilder {
+            options: ShapeOptions::default(),
+            divisions: 8,
+            projection: CubeSphereProjection::Normalized,
+            texcoords: CubeSphereTexcoords::PerFace,
+        }
+    }
+}
+
+impl CubeSphereBuilder {
+    /// Create a new `CubeSphereBuilder` object.
+    pub fn new() -> CubeSphereBuilder {
+        Default::default()
+    }
+
+    /// Specify the number of divisions to make along each edge of each face of
+    /// the cube. By default, the builder will use 8 divisions.
+    pub fn with_divisions(mut self, divisions: usize) -> Self {
+        self.divisions = divisions;
+        self
+    }
+
+    /// Specify how the grid on each face is projected onto the sphere. By default,
+    /// the builder will use `CubeSphereProjection::Normalized`.
+    pub fn with_projection(mut self, projection: CubeSphereProjection) -> Self {
+        self.projection = projection;
+        self
+    }
+
+    /// Specify how texture coordinates are laid out. By default, the builder will
+    /// use `CubeSphereTexcoords::PerFace`.
+    pub fn with_texcoords(mut self, texcoords: CubeSphereTexcoords) -> Self {
+        self.texcoords = texcoords;
+        self
+    }
+
+    /// Specify how vertex normals are generated. By default, the builder will
+    /// use `Shading::Faceted`.
+    pub fn with_shading(mut self, shading: Shading) -> Self {
+        self.options.shading = shading;
+        self
+    }
+
+    /// Returns the total number of vertices in each face of the cube. Each cell of
+    /// the grid is made up of two triangles.
+    pub fn num_vertices_per_side(&self) -> usize {
+        self.divisions * self.divisions * 6
+    }
+}
+
+impl ShapeBuilder for CubeSphereBuilder {
+    fn options(&self) -> &ShapeOptions {
+        &self.options
+    }
+
+    fn options_mut(&mut self) -> &mut ShapeOptions {
+        &mut self.options
+    }
+
+    fn primitives(&self) -> index::PrimitiveType {
+        index::PrimitiveType::TrianglesList
+    }
+
+    fn build_unindexed_vertices(&self) -> Result<Vec<Vertex>, ShapeCreationError> {
+        // Ensure there are enough divisions to produce valid geometry.
+        if self.divisions < 1 {
+            return Err(ShapeCreationError::NotEnoughDivisionsInU);
+        }
+
+        let num_sides = 6;
+        let divisions = self.divisions as f32;
+
+        // Compute the normal transformation matrix.
+        let normal_matrix = self.options.normal_matrix();
+
+        // Build vertex array.
+        let total_num_verts = self.num_unindexed_vertices();
+        let mut vertices = Vec::<Vertex>::with_capacity(total_num_verts);
+
+        for side in 0..num_sides {
+            // Look up the corners of this side of the cube.
+            let corners = [
+                corner_position(INDEX_LUT[side * 4]),
+                corner_position(INDEX_LUT[side * 4 + 1]),
+                corner_position(INDEX_LUT[side * 4 + 2]),
+                corner_position(INDEX_LUT[side * 4 + 3]),
+            ];
+
+            // Compute the position of grid point (i, j) on the sphere.
+            let point = |i: usize, j: usize| {
+                let (s, t) = (i as f32 / divisions, j as f32 / divisions);
+                let bottom = corners[0].lerp(corners[1], s);
+                let top = corners[2].lerp(corners[3], s);
+                let cube = bottom.lerp(top, t);
+                match self.projection {
+                    CubeSphereProjection::Normalized => cube.normalize(),
+                    CubeSphereProjection::EqualArea => equal_area_projection(cube, side),
+                }
+            };
+
+            // Compute the texture coordinate of grid point (i, j).
+            let texcoord = |i: usize, j: usize| {
+                let (s, t) = (i as f32 / divisions, j as f32 / divisions);
+                match self.texcoords {
+                    CubeSphereTexcoords::PerFace => [s, t],
+                    CubeSphereTexcoords::Atlas => {
+                        [((side % 3) as f32 + s) / 3.0, ((side / 3) as f32 + t) / 2.0]
+                    }
+                }
+            };
+
+            for j in 0..self.divisions {
+                for i in 0..self.divisions {
+                    // Compute cell vertices
+                    let lut_coords = [(i, j), (i + 1, j), (i, j + 1), (i + 1, j + 1)];
+                    let verts = [
+                        point(i, j),
+                        point(i + 1, j),
+                        point(i, j + 1),
+                        point(i + 1, j + 1),
+                    ];
+
+                    // Emit vertices.
+                    for tri in POLY_LUT.chunks(3) {
+                        let v0 = &verts[tri[0]];
+                        let v1 = &verts[tri[1]];
+                        let v2 = &verts[tri[2]];
+                        let face_normal = (v1 - v0).cross(v2 - v0).normalize();
+
+                        for index in tri {
+                            let vpos = verts[*index];
+                            let pos = self.options.matrix * vpos.extend(1.0);
+                            let (i, j) = lut_coords[*index];
+                            let normal = match self.options.shading {
+                                Shading::Faceted => face_normal,
+                                Shading::Smooth => vpos,
+                            };
+                            vertices.push(Vertex {
+                                position: Point3::<f32>::from_homogeneous(pos).into(),
+                                normal: (normal_matrix * normal).normalize().into(),
+                                texcoord: texcoord(i, j),
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        assert!(vertices.len() == total_num_verts);
+        Ok(vertices)
+    }
+
+    fn num_unindexed_vertices(&self) -> usize {
+        self.num_vertices_per_side() * 6
+    }
+}
+
+/// Project a point on the given side of the unit-cube onto the unit-sphere, using
+/// the area-preserving mapping described by Roşca and Plonka ("Uniform spherical
+/// grids via equal area projection from the cube to the sphere", 2011).
+fn equal_area_projection(cube: Vector3<f32>, side: usize) -> Vector3<f32> {
+    // Find the axis that this side faces along, and the two axes that span it,
+    // and express the point in normalized coordinates on the face.
+    let axis = side / 2;
+    let sign = (((side % 2) * 2) as f32) - 1.0;
+    let (a, b) = ((axis + 1) % 3, (axis + 2) % 3);
+    let (alpha, beta) = (cube[a] * 2.0, cube[b] * 2.0);
+
+    // Map the square face onto a disc with the same area as the spherical cap it
+    // covers. Each face is split into four triangles about its centre, in which
+    // the dominant coordinate determines the radius.
+    let curve = |major: f32, minor: f32| {
+        if major == 0.0 {
+            return (0.0, 0.0);
+        }
+        let angle = minor * f32::consts::PI / (12.0 * major);
+        let scale = f32::consts::SQRT_2.sqrt() * major / (f32::consts::SQRT_2 - angle.cos()).sqrt();
+        (
+            scale * (f32::consts::SQRT_2 * angle.cos() - 1.0),
+            scale * f32::consts::SQRT_2 * angle.sin(),
+        )
+    };
+    let (x, y) = if beta.abs() <= alpha.abs() {
+        curve(alpha, beta)
+    } else {
+        let (y, x) = curve(beta, alpha);
+        (x, y)
+    };
+
+    // Map the disc onto the sphere using the inverse Lambert azimuthal equal-area
+    // projection.
+    let r2 = x * x + y * y;
+    let planar = (1.0 - r2 * 0.25).max(0.0).sqrt();
+    let mut result = Vector3::<f32>::zero();
+    result[a] = x * planar;
+    result[b] = y * planar;
+    result[axis] = sign * (1.0 - r2 * 0.5);
+    result
+}
+
+#[cfg(test)]
+fn cell_areas(vertices: &[Vertex]) -> Vec<f32> {
+    vertices
+        .chunks(6)
+        .map(|chunk| {
+            chunk
+                .chunks(3)
+                .map(|tri| {
+                    let v0 = Vector3::<f32>::from(tri[0].position);
+                    let v1 = Vector3::<f32>::from(tri[1].position);
+                    let v2 = Vector3::<f32>::from(tri[2].position);
+                    (v1 - v0).cross(v2 - v0).magnitude() * 0.5
+                })
+                .sum()
+        })
+        .collect()
+}
+
+#[test]
+pub fn ensure_default_cubesphere_is_unit_sphere() {
+    for projection in &[
+        CubeSphereProjection::Normalized,
+        CubeSphereProjection::EqualArea,
+    ] {
+        let vertices = CubeSphereBuilder::new()
+            .with_projection(*projection)
+            .build_vertices()
+            .expect("Failed to build vertices");
+        for vertex in &vertices {
+            assert_ulps_eq!(
+                Vector3::<f32>::from(vertex.position).magnitude(),
+                1.0,
+                epsilon = 0.0001
+            );
+        }
+    }
+}
+
+#[test]
+pub fn ensure_default_cubesphere_has_centroid_at_origin() {
+    let vertices = CubeSphereBuilder::new()
+        .build_vertices()
+        .expect("Failed to build vertices");
+    let mut sum = Vector3::<f32>::zero();
+    for vertex in &vertices {
+        sum += Vector3::<f32>::from(vertex.position);
+    }
+    assert_ulps_eq!(sum, Vector3::<f32>::zero(), epsilon = 0.001);
+}
+
+#[test]
+pub fn ensure_default_cubesphere_has_outward_facing_normals() {
+    let vertices = CubeSphereBuilder::new()
+        .scale(2.0, 2.0, 2.0)
+        .build_vertices()
+        .expect("Failed to build vertices");
+    for vertex in &vertices {
+        let position = Vector3::<f32>::from(vertex.position);
+        let normal = Vector3::<f32>::from(vertex.normal);
+        assert!(position.dot(normal) > 0.0);
+    }
+}
+
+#[test]
+pub fn ensure_default_cubesphere_has_ccw_triangles() {
+    let vertices = CubeSphereBuilder::new()
+        .with_projection(CubeSphereProjection::EqualArea)
+        .build_vertices()
+        .expect("Failed to build vertices");
+    for chunk in vertices.chunks(3) {
+        let v0 = Vector3::<f32>::from(chunk[0].position);
+        let v1 = Vector3::<f32>::from(chunk[1].position);
+        let v2 = Vector3::<f32>::from(chunk[2].position);
+        let eyepos = v0 + Vector3::<f32>::from(chunk[0].normal);
+        let e0 = v1 - v0;
+        let e1 = v2 - v0;
+        let n = e0.cross(e1);
+        assert!(n.dot(v0 - eyepos) <= 0.0);
+        assert!(n.dot(v1 - eyepos) <= 0.0);
+        assert!(n.dot(v2 - eyepos) <= 0.0);
+    }
+}
+
+#[test]
+pub fn ensure_default_cubesphere_has_faceted_normals() {
+    let vertices = CubeSphereBuilder::new()
+        .build_vertices()
+        .expect("Failed to build vertices");
+
+    for chunk in vertices.chunks(3) {
+        let v0 = Vector3::<f32>::from(chunk[0].position);
+        let v1 = Vector3::<f32>::from(chunk[1].position);
+        let v2 = Vector3::<f32>::from(chunk[2].position);
+        let n0 = Vector3::<f32>::from(chunk[0].normal);
+        let n1 = Vector3::<f32>::from(chunk[1].normal);
+        let n2 = Vector3::<f32>::from(chunk[2].normal);
+        let e0 = v1 - v0;
+        let e1 = v2 - v0;
+        let n = e0.cross(e1).normalize();
+        assert_ulps_eq!(n, n0, epsilon = 0.0001);
+        assert_ulps_eq!(n, n1, epsilon = 0.0001);
+        assert_ulps_eq!(n, n2, epsilon = 0.0001);
+    }
+}
+
+#[test]
+pub fn ensure_smooth_cubesphere_has_radial_normals() {
+    let vertices = CubeSphereBuilder::new()
+        .with_shading(Shading::Smooth)
+        .build_vertices()
+        .expect("Failed to build vertices");
+    for vertex in &vertices {
+        let position = Vector3::<f32>::from(vertex.position);
+        let normal = Vector3::<f32>::from(vertex.normal);
+        assert_ulps_eq!(position, normal, epsilon = 0.0001);
+    }
+}
+
+#[test]
+pub fn ensure_cubesphere_texcoords_are_laid_out_per_face_or_in_atlas() {
+    let builder = CubeSphereBuilder::new().with_divisions(4);
+    let per_face = builder.build_vertices().expect("Failed to build vertices");
+    let atlas = builder
+        .with_texcoords(CubeSphereTexcoords::Atlas)
+        .build_vertices()
+        .expect("Failed to build vertices");
+    let verts_per_side = per_face.len() / 6;
+    for (index, (a, b)) in per_face.iter().zip(atlas.iter()).enumerate() {
+        let side = index / verts_per_side;
+        let cell = [(side % 3) as f32, (side / 3) as f32];
+        assert!(a.texcoord[0] >= 0.0 && a.texcoord[0] <= 1.0);
+        assert!(a.texcoord[1] >= 0.0 && a.texcoord[1] <= 1.0);
+        assert_ulps_eq!(b.texcoord[0], (cell[0] + a.texcoord[0]) / 3.0);
+        assert_ulps_eq!(b.texcoord[1], (cell[1] + a.texcoord[1]) / 2.0);
+    }
+}
+
+#[test]
+pub fn ensure_equal_area_cubesphere_has_uniform_cells() {
+    let builder = CubeSphereBuilder::new().with_divisions(16);
+    let normalized = cell_areas(&builder.build_vertices().expect("Failed to build vertices"));
+    let equal_area = cell_areas(
+        &builder
+            .with_projection(CubeSphereProjection::EqualArea)
+            .build_vertices()
+            .expect("Failed to build vertices"),
+    );
+    let ratio = |areas: &[f32]| {
+        let min = areas.iter().cloned().fold(f32::MAX, f32::min);
+        let max = areas.iter().cloned().fold(f32::MIN, f32::max);
+        max / min
+    };
+    assert!(ratio(&equal_area) < 1.1);
+    assert!(ratio(&equal_area) < ratio(&normalized));
+}
+
+#[test]
+pub fn ensure_cubesphere_with_too_few_divisions_fails() {
+    match CubeSphereBuilder::new().with_divisions(0).build_vertices() {
+        Err(ShapeCreationError::NotEnoughDivisionsInU) => {}
+        _ => panic!("Expected NotEnoughDivisionsInU"),
+    }
+}
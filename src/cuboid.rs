@@ -1,33 +1,50 @@
 //! A module for constructing cuboid shapes.
 
 extern crate cgmath;
+#[cfg(feature = "glium")]
 extern crate glium;
 
 use self::cgmath::*;
 use crate::errors::ShapeCreationError;
+use crate::index;
+#[cfg(feature = "glium")]
+use crate::mesh::Mesh;
+use crate::shape::{ShapeBuilder, ShapeOptions};
+use crate::tangent::TangentSpace;
 use crate::vertex::Vertex;
 
 /// A polygonal `Cuboid` object.
 ///
 /// This object is constructed using a `CuboidBuilder` object.
-pub struct Cuboid {
-    vertices: glium::vertex::VertexBufferAny,
-}
+#[cfg(feature = "glium")]
+pub type Cuboid = Mesh;
 
-/// Allows a `Cuboid` object to be passed as a source of vertices.
-impl<'a> From<&'a Cuboid> for glium::vertex::VerticesSource<'a> {
-    fn from(cuboid: &'a Cuboid) -> glium::vertex::VerticesSource<'a> {
-        (&cuboid.vertices).into()
-    }
-}
+/// Lookup-table of the corners of each side of the cuboid, in the order -X, +X,
+/// -Y, +Y, -Z, +Z. Each corner is encoded as described by `corner_position`,
+/// and the four corners of each side correspond to the texture coordinates
+/// (0, 0), (1, 0), (0, 1), and (1, 1) respectively.
+pub(crate) const INDEX_LUT: [usize; 24] = [
+    0, 4, 1, 5, // -X
+    6, 2, 7, 3, // +X
+    0, 2, 4, 6, // -Y
+    5, 7, 1, 3, // +Y
+    2, 0, 3, 1, // -Z
+    4, 6, 5, 7, // +Z
+];
 
-/// Allows a `Cuboid` object to be passed as a source of indices.
-impl<'a> Into<glium::index::IndicesSource<'a>> for &'a Cuboid {
-    fn into(self) -> glium::index::IndicesSource<'a> {
-        glium::index::IndicesSource::NoIndices {
-            primitives: glium::index::PrimitiveType::TrianglesList,
-        }
-    }
+/// Lookup-table of the corners (as indices into each side of `INDEX_LUT`) of
+/// the two counter-clock-wise triangles that make up each side of the cuboid.
+pub(crate) const POLY_LUT: [usize; 6] = [0, 1, 2, 2, 1, 3];
+
+/// Returns the position of an encoded corner of the unit-cube, where bit 1
+/// selects the x coordinate, bit 0 selects the y coordinate, and bit 2 selects
+/// the z coordinate.
+pub(crate) fn corner_position(coord: usize) -> Vector3<f32> {
+    Vector3::<f32>::new(
+        ((coord & 2) as f32 - 1.0) * 0.5,
+        (((coord & 1) * 2) as f32 - 1.0) * 0.5,
+        (((coord >> 1) & 2) as f32 - 1.0) * 0.5,
+    )
 }
 
 /// Responsible for building and returning a `Cuboid` object.
@@ -42,13 +59,16 @@ impl<'a> Into<glium::index::IndicesSource<'a>> for &'a Cuboid {
 /// respective face (such that the shape appears faceted when lit). Vertex
 /// texture coordinates define a planar-projection on each face.
 pub struct CuboidBuilder {
-    matrix: cgmath::Matrix4<f32>,
+    options: ShapeOptions,
 }
 
 impl Default for CuboidBuilder {
     fn default() -> Self {
         CuboidBuilder {
-            matrix: cgmath::Matrix4::<f32>::identity(),
+            options: ShapeOptions {
+                tangent_space: TangentSpace::Cuboid,
+                ..Default::default()
+            },
         }
     }
 }
@@ -58,117 +78,27 @@ impl CuboidBuilder {
     pub fn new() -> CuboidBuilder {
         Default::default()
     }
+}
 
-    /// Apply a scaling transformation to the shape.
-    ///
-    /// The `scale`, `translate`, and `rotate` functions accumulate, and are
-    /// not commutative. The transformation functions are intended to provide
-    /// flexibility in model-space. For per-instance world-space transformations,
-    /// one should prefer to share as few shapes as possible across multiple
-    /// instances, and instead rely on uniform constants in the shader and/or
-    /// instanced drawing.
-    pub fn scale(mut self, x: f32, y: f32, z: f32) -> Self {
-        self.matrix = cgmath::Matrix4::from_nonuniform_scale(x, y, z) * self.matrix;
-        self
-    }
-
-    /// Apply a translation transformation to the shape.
-    ///
-    /// The `scale`, `translate`, and `rotate` functions accumulate, and are
-    /// not commutative. The transformation functions are intended to provide
-    /// flexibility in model-space. For per-instance world-space transformations,
-    /// one should prefer to share as few shapes as possible across multiple
-    /// instances, and instead rely on uniform constants in the shader and/or
-    /// instanced drawing.
-    pub fn translate(mut self, x: f32, y: f32, z: f32) -> Self {
-        self.matrix = cgmath::Matrix4::from_translation([x, y, z].into()) * self.matrix;
-        self
-    }
-
-    /// Apply a rotation transformation to the shape about the x-axis.
-    ///
-    /// The `scale`, `translate`, and `rotate` functions accumulate, and are
-    /// not commutative. The transformation functions are intended to provide
-    /// flexibility in model-space. For per-instance world-space transformations,
-    /// one should prefer to share as few shapes as possible across multiple
-    /// instances, and instead rely on uniform constants in the shader and/or
-    /// instanced drawing.
-    pub fn rotate_x(mut self, radians: f32) -> Self {
-        self.matrix = cgmath::Matrix4::<f32>::from(cgmath::Matrix3::<f32>::from_angle_x(
-            cgmath::Rad::<f32>(radians),
-        )) * self.matrix;
-        self
-    }
-
-    /// Apply a rotation transformation to the shape about the y-axis.
-    ///
-    /// The `scale`, `translate`, and `rotate` functions accumulate, and are
-    /// not commutative. The transformation functions are intended to provide
-    /// flexibility in model-space. For per-instance world-space transformations,
-    /// one should prefer to share as few shapes as possible across multiple
-    /// instances, and instead rely on uniform constants in the shader and/or
-    /// instanced drawing.
-    pub fn rotate_y(mut self, radians: f32) -> Self {
-        self.matrix = cgmath::Matrix4::<f32>::from(cgmath::Matrix3::<f32>::from_angle_y(
-            cgmath::Rad::<f32>(radians),
-        )) * self.matrix;
-        self
+impl ShapeBuilder for CuboidBuilder {
+    fn options(&self) -> &ShapeOptions {
+        &self.options
     }
 
-    /// Apply a rotation transformation to the shape about the z-axis.
-    ///
-    /// The `scale`, `translate`, and `rotate` functions accumulate, and are
-    /// not commutative. The transformation functions are intended to provide
-    /// flexibility in model-space. For per-instance world-space transformations,
-    /// one should prefer to share as few shapes as possible across multiple
-    /// instances, and instead rely on uniform constants in the shader and/or
-    /// instanced drawing.
-    pub fn rotate_z(mut self, radians: f32) -> Self {
-        self.matrix = cgmath::Matrix4::<f32>::from(cgmath::Matrix3::<f32>::from_angle_z(
-            cgmath::Rad::<f32>(radians),
-        )) * self.matrix;
-        self
+    fn options_mut(&mut self) -> &mut ShapeOptions {
+        &mut self.options
     }
 
-    /// Build a new `Cuboid` object.
-    pub fn build<F>(self, display: &F) -> Result<Cuboid, ShapeCreationError>
-    where
-        F: glium::backend::Facade,
-    {
-        let vertices = &self.build_vertices()?;
-        let vbuffer = glium::vertex::VertexBuffer::<Vertex>::new(display, vertices)?;
-        Ok(Cuboid {
-            vertices: glium::vertex::VertexBufferAny::from(vbuffer),
-        })
+    fn primitives(&self) -> index::PrimitiveType {
+        index::PrimitiveType::TrianglesList
     }
 
-    /// Build the shape vertices and return them in a vector.
-    ///
-    /// Useful if you wish to do other things with the vertices besides constructing
-    /// a `Cuboid` object (e.g. unit testing, further processing, etc).
-    pub fn build_vertices(&self) -> Result<Vec<Vertex>, ShapeCreationError> {
-        // Define lookup-tables used during construction of the cuboid geometry
-        let index_lut = [
-            0, 4, 1, 5, // -X
-            6, 2, 7, 3, // +X
-            0, 2, 4, 6, // -Y
-            5, 7, 1, 3, // +Y
-            2, 0, 3, 1, // -Z
-            4, 6, 5, 7, // +Z
-        ];
-        let poly_lut = [0, 1, 2, 2, 1, 3];
+    fn build_unindexed_vertices(&self) -> Result<Vec<Vertex>, ShapeCreationError> {
         let num_sides = 6;
         let verts_per_side = 6;
 
         // Compute the normal transformation matrix.
-        let normal_matrix = Matrix3::<f32>::from_cols(
-            self.matrix.x.truncate(),
-            self.matrix.y.truncate(),
-            self.matrix.z.truncate(),
-        )
-        .invert()
-        .unwrap_or_else(Matrix3::<f32>::identity)
-        .transpose();
+        let normal_matrix = self.options.normal_matrix();
 
         // Generate cuboid vertices.
         let mut vertices = Vec::<Vertex>::with_capacity(verts_per_side * num_sides);
@@ -180,23 +110,22 @@ impl CuboidBuilder {
 
             // Build side vertices.
             for vert in 0..verts_per_side {
-                let coord = index_lut[poly_lut[vert] + (side * 4)];
-                let vpos = Vector4::<f32>::new(
-                    (((coord & 2) - 1) as f32) * 0.5,
-                    (((coord & 1) * 2 - 1) as f32) * 0.5,
-                    ((((coord >> 1) & 2) - 1) as f32) * 0.5,
-                    1.0,
-                );
+                let coord = INDEX_LUT[POLY_LUT[vert] + (side * 4)];
+                let vpos = corner_position(coord).extend(1.0);
                 vertices.push(Vertex {
-                    position: Point3::<f32>::from_homogeneous(self.matrix * vpos).into(),
+                    position: Point3::<f32>::from_homogeneous(self.options.matrix * vpos).into(),
                     normal: (normal_matrix * normal).normalize().into(),
-                    texcoord: [(poly_lut[vert] % 2) as f32, (poly_lut[vert] / 2) as f32],
+                    texcoord: [(POLY_LUT[vert] % 2) as f32, (POLY_LUT[vert] / 2) as f32],
                 });
             }
         }
 
         Ok(vertices)
     }
+
+    fn num_unindexed_vertices(&self) -> usize {
+        6 * 6
+    }
 }
 
 #[test]
@@ -296,3 +225,75 @@ pub fn ensure_default_cuboid_has_faceted_normals() {
         assert_ulps_eq!(n, n2);
     }
 }
+
+#[test]
+pub fn ensure_cuboid_tangents_are_transformed_by_builder_matrix() {
+    let vertices = CuboidBuilder::new()
+        .build_tangent_vertices()
+        .expect("Failed to build vertices");
+    let rotated = CuboidBuilder::new()
+        .scale(2.0, 3.0, 4.0)
+        .rotate_y(0.5)
+        .build_tangent_vertices()
+        .expect("Failed to build vertices");
+    let rotation = Matrix3::<f32>::from_angle_y(Rad(0.5));
+    for (a, b) in vertices.iter().zip(rotated.iter()) {
+        let tangent = Vector4::<f32>::from(a.tangent);
+        let expected = (rotation * tangent.truncate()).extend(tangent.w);
+        assert_ulps_eq!(Vector4::<f32>::from(b.tangent), expected, epsilon = 0.0001);
+    }
+}
+
+#[test]
+pub fn ensure_flipped_cuboid_has_inward_facing_normals() {
+    let vertices = CuboidBuilder::new()
+        .scale(2.0, 2.0, 2.0)
+        .flip_faces()
+        .build_vertices()
+        .expect("Failed to build vertices");
+    for vertex in &vertices {
+        let position = Vector3::<f32>::from(vertex.position);
+        let normal = Vector3::<f32>::from(vertex.normal);
+        let inside = position + normal;
+        assert!(inside.x.abs() <= position.x.abs());
+        assert!(inside.y.abs() <= position.y.abs());
+        assert!(inside.z.abs() <= position.z.abs());
+    }
+}
+
+#[test]
+pub fn ensure_cubemap_cuboid_texcoords_are_untransformed_directions() {
+    let vertices = CuboidBuilder::new()
+        .with_cubemap_texcoords()
+        .scale(10.0, 20.0, 30.0)
+        .translate(1.0, 2.0, 3.0)
+        .build_vertices_as::<crate::vertex::CubemapVertex>()
+        .expect("Failed to build vertices");
+    for vertex in &vertices {
+        let direction = Vector3::<f32>::from(vertex.texcoord);
+        let position = Vector3::<f32>::from(vertex.position) - Vector3::new(1.0, 2.0, 3.0);
+        assert_abs_diff_eq!(
+            direction,
+            Vector3::new(position.x / 10.0, position.y / 20.0, position.z / 30.0),
+            epsilon = 0.0001
+        );
+        assert_abs_diff_eq!(direction.x.abs(), 0.5, epsilon = 0.0001);
+    }
+}
+
+#[test]
+pub fn ensure_inward_facing_cuboid_matches_flipped_cuboid() {
+    let flipped = CuboidBuilder::new()
+        .flip_faces()
+        .build_vertices()
+        .expect("Failed to build vertices");
+    let inward = CuboidBuilder::new()
+        .inward_facing()
+        .build_vertices()
+        .expect("Failed to build vertices");
+    for (a, b) in flipped.iter().zip(inward.iter()) {
+        assert_eq!(a.position, b.position);
+        assert_eq!(a.normal, b.normal);
+        assert_eq!(a.texcoord, b.texcoord);
+    }
+}
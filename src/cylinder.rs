@@ -0,0 +1,396 @@
+//! A module for constructing cylinder shapes.
+
+extern crate cgmath;
+#[cfg(feature = "glium")]
+extern crate glium;
+
+use self::cgmath::*;
+use crate::errors::ShapeCreationError;
+use crate::index;
+#[cfg(feature = "glium")]
+use crate::mesh::Mesh;
+use crate::shading::Shading;
+use crate::shape::{ShapeBuilder, ShapeOptions};
+use crate::vertex::Vertex;
+
+use std::f32;
+
+/// A polygonal `Cylinder` object.
+///
+/// This object is constructed using a `CylinderBuilder` object.
+#[cfg(feature = "glium")]
+pub type Cylinder = Mesh;
+
+/// Responsible for building and returning a `Cylinder` object.
+///
+/// By default, the cylinder has a radius of 1 and a height of 2, with its
+/// centre-of-mass located at the origin and its axis aligned to the y-axis.
+/// Both ends of the cylinder are closed with a cap. This can be overriden
+/// using the transformation methods on this object.
+///
+/// The resultant geometry is constructed to suit OpenGL defaults - assuming
+/// a right-handed coordinate system, front-facing polygons are defined in
+/// counter-clock-wise order. By default, vertex normals point in the direction
+/// of their respective face (such that the shape appears faceted when lit),
+/// although smooth normals can be requested instead. Vertex
+/// texture coordinates define a cylindrical-projection on the sides of the
+/// object, and a planar-projection on each cap.
+pub struct CylinderBuilder {
+    options: ShapeOptions,
+    radial_divisions: usize,
+    height_divisions: usize,
+    top_cap: bool,
+    bottom_cap: bool,
+}
+
+impl Default for CylinderBuilder {
+    fn default() -> Self {
+        CylinderBuilder {
+            options: ShapeOptions::default(),
+            radial_divisions: 24,
+            height_divisions: 1,
+            top_cap: true,
+            bottom_cap: true,
+        }
+    }
+}
+
+impl CylinderBuilder {
+    /// Create a new `CylinderBuilder` object.
+    pub fn new() -> CylinderBuilder {
+        Default::default()
+    }
+
+    /// Specify the number of divisions to make around the circumference of
+    /// the cylinder (radial), and along its length (height). By default, the
+    /// builder will use 24 radial divisions and 1 height division.
+    pub fn with_divisions(mut self, radial: usize, height: usize) -> Self {
+        self.radial_divisions = radial;
+        self.height_divisions = height;
+        self
+    }
+
+    /// Specify whether the top and bottom ends of the cylinder are closed
+    /// with a cap. By default, both ends are capped.
+    pub fn with_caps(mut self, top: bool, bottom: bool) -> Self {
+        self.top_cap = top;
+        self.bottom_cap = bottom;
+        self
+    }
+
+    /// Remove both caps, such that the resultant cylinder is a tube that
+    /// is open at both ends. Equivalent to `with_caps(false, false)`.
+    pub fn open_ended(self) -> Self {
+        self.with_caps(false, false)
+    }
+
+    /// Specify how vertex normals are generated. By default, the builder will
+    /// use `Shading::Faceted`. The caps are always flat.
+    pub fn with_shading(mut self, shading: Shading) -> Self {
+        self.options.shading = shading;
+        self
+    }
+
+    /// Returns the number of caps in the resultant cylinder geometry. The resultant value
+    /// will depend on the caps specified on the builder.
+    pub fn num_caps(&self) -> usize {
+        self.top_cap as usize + self.bottom_cap as usize
+    }
+
+    /// Returns the number of vertices generated for each cap face. The current implementation
+    /// will always return 3.
+    pub fn num_vertices_per_cap_face(&self) -> usize {
+        3
+    }
+
+    /// Returns the total number of vertices in each cap.
+    pub fn num_vertices_per_cap(&self) -> usize {
+        self.num_vertices_per_cap_face() * self.radial_divisions
+    }
+
+    /// Returns the number of vertical slices in the resultant cylinder geometry. The resultant
+    /// value will depend on the number of height divisions specified on the builder.
+    pub fn num_slices(&self) -> usize {
+        self.height_divisions
+    }
+
+    /// Returns the total number of vertices in each vertical slice face (e.g. excluding caps).
+    /// The current implementation will always return 6.
+    pub fn num_vertices_per_slice_face(&self) -> usize {
+        6
+    }
+
+    /// Returns the total number of vertices in each vertical slice.
+    pub fn num_vertices_per_slice(&self) -> usize {
+        self.num_vertices_per_slice_face() * self.radial_divisions
+    }
+}
+
+impl ShapeBuilder for CylinderBuilder {
+    fn options(&self) -> &ShapeOptions {
+        &self.options
+    }
+
+    fn options_mut(&mut self) -> &mut ShapeOptions {
+        &mut self.options
+    }
+
+    fn primitives(&self) -> index::PrimitiveType {
+        index::PrimitiveType::TrianglesList
+    }
+
+    fn build_unindexed_vertices(&self) -> Result<Vec<Vertex>, ShapeCreationError> {
+        // Ensure there are enough divisions around and along the cylinder to
+        // produce valid geometry.
+        if self.radial_divisions < 3 {
+            return Err(ShapeCreationError::NotEnoughDivisionsInU);
+        }
+
+        if self.height_divisions < 1 {
+            return Err(ShapeCreationError::NotEnoughDivisionsInV);
+        }
+
+        // Build lookup tables.
+        let u_angle = 2.0 * f32::consts::PI / self.radial_divisions as f32;
+
+        fn sin_cos(val: f32) -> [f32; 2] {
+            [val.sin(), val.cos()]
+        }
+
+        let u_tab = (0..=self.radial_divisions)
+            .map(|x| sin_cos(((x % self.radial_divisions) as f32) * u_angle))
+            .collect::<Vec<[f32; 2]>>();
+
+        let y_tab = (0..=self.height_divisions)
+            .map(|x| 1.0 - 2.0 * (x as f32) / (self.height_divisions as f32))
+            .collect::<Vec<f32>>();
+
+        let indices = [0, 1, 2, 2, 1, 3];
+
+        // Compute the normal transformation matrix.
+        let normal_matrix = self.options.normal_matrix();
+
+        // Build vertex array.
+        let total_num_verts = self.num_unindexed_vertices();
+        let mut vertices = Vec::<Vertex>::with_capacity(total_num_verts);
+
+        let mut emit = |vpos: Vector3<f32>, normal: Vector3<f32>, texcoord: [f32; 2]| {
+            let pos = self.options.matrix * vpos.extend(1.0);
+            vertices.push(Vertex {
+                position: Point3::<f32>::from_homogeneous(pos).into(),
+                normal: (normal_matrix * normal).normalize().into(),
+                texcoord,
+            });
+        };
+
+        let cap_texcoord = |vpos: &Vector3<f32>| [vpos.x * 0.5 + 0.5, vpos.z * 0.5 + 0.5];
+
+        // Emit the top cap.
+        if self.top_cap {
+            let centre = Vector3::<f32>::new(0.0, 1.0, 0.0);
+            let normal = Vector3::<f32>::unit_y();
+            for u in 0..self.radial_divisions {
+                let rim = [
+                    Vector3::<f32>::new(u_tab[u + 1][1], 1.0, u_tab[u + 1][0]),
+                    Vector3::<f32>::new(u_tab[u][1], 1.0, u_tab[u][0]),
+                ];
+                emit(centre, normal, cap_texcoord(&centre));
+                emit(rim[0], normal, cap_texcoord(&rim[0]));
+                emit(rim[1], normal, cap_texcoord(&rim[1]));
+            }
+        }
+
+        // Emit the sides.
+        for v in 0..self.height_divisions {
+            for u in 0..self.radial_divisions {
+                // Compute slice vertices
+                let verts = [
+                    Vector3::<f32>::new(u_tab[u + 1][1], y_tab[v], u_tab[u + 1][0]),
+                    Vector3::<f32>::new(u_tab[u + 1][1], y_tab[v + 1], u_tab[u + 1][0]),
+                    Vector3::<f32>::new(u_tab[u][1], y_tab[v], u_tab[u][0]),
+                    Vector3::<f32>::new(u_tab[u][1], y_tab[v + 1], u_tab[u][0]),
+                ];
+
+                let lut_coords = [(u + 1, v), (u + 1, v + 1), (u, v), (u, v + 1)];
+
+                // Compute face normal
+                let normal = (verts[1] - verts[0]).cross(verts[2] - verts[0]).normalize();
+
+                // Emit vertices.
+                for index in &indices {
+                    let (u, v) = lut_coords[*index];
+                    let normal = match self.options.shading {
+                        Shading::Faceted => normal,
+                        Shading::Smooth => Vector3::<f32>::new(u_tab[u][1], 0.0, u_tab[u][0]),
+                    };
+                    emit(
+                        verts[*index],
+                        normal,
+                        [
+                            u as f32 / self.radial_divisions as f32,
+                            v as f32 / self.height_divisions as f32,
+                        ],
+                    );
+                }
+            }
+        }
+
+        // Emit the bottom cap.
+        if self.bottom_cap {
+            let centre = Vector3::<f32>::new(0.0, -1.0, 0.0);
+            let normal = -Vector3::<f32>::unit_y();
+            for u in 0..self.radial_divisions {
+                let rim = [
+                    Vector3::<f32>::new(u_tab[u][1], -1.0, u_tab[u][0]),
+                    Vector3::<f32>::new(u_tab[u + 1][1], -1.0, u_tab[u + 1][0]),
+                ];
+                emit(centre, normal, cap_texcoord(&centre));
+                emit(rim[0], normal, cap_texcoord(&rim[0]));
+                emit(rim[1], normal, cap_texcoord(&rim[1]));
+            }
+        }
+
+        assert!(vertices.len() == total_num_verts);
+        Ok(vertices)
+    }
+
+    fn num_unindexed_vertices(&self) -> usize {
+        (self.num_vertices_per_slice() * self.num_slices())
+            + (self.num_vertices_per_cap() * self.num_caps())
+    }
+}
+
+#[test]
+pub fn ensure_default_cylinder_has_unit_radius_and_height_of_two() {
+    let vertices = CylinderBuilder::new()
+        .build_vertices()
+        .expect("Failed to build vertices");
+    for vertex in &vertices {
+        let pos = Vector3::<f32>::from(vertex.position);
+        assert!(Vector2::<f32>::new(pos.x, pos.z).magnitude() <= 1.0 + 1e-6);
+        assert!(abs_diff_eq!(pos.y.abs(), 1.0));
+    }
+}
+
+#[test]
+pub fn ensure_default_cylinder_has_centroid_at_origin() {
+    let vertices = CylinderBuilder::new()
+        .build_vertices()
+        .expect("Failed to build vertices");
+    let mut sum = Vector3::<f32>::zero();
+    for vertex in &vertices {
+        sum += Vector3::<f32>::from(vertex.position);
+    }
+    assert_ulps_eq!(sum, Vector3::<f32>::zero(), epsilon = 0.0001);
+}
+
+#[test]
+pub fn ensure_default_cylinder_has_outward_facing_normals() {
+    let vertices = CylinderBuilder::new()
+        .scale(2.0, 2.0, 2.0)
+        .build_vertices()
+        .expect("Failed to build vertices");
+    for vertex in &vertices {
+        let position = Vector3::<f32>::from(vertex.position);
+        let normal = Vector3::<f32>::from(vertex.normal);
+        let outside = position + normal;
+        assert!(outside.x.abs() >= position.x.abs());
+        assert!(outside.y.abs() >= position.y.abs());
+        assert!(outside.z.abs() >= position.z.abs());
+    }
+}
+
+#[test]
+pub fn ensure_default_cylinder_has_uvs_in_unit_range() {
+    let vertices = CylinderBuilder::new()
+        .with_divisions(4, 4)
+        .build_vertices()
+        .expect("Failed to build vertices");
+    for vertex in &vertices {
+        assert!(vertex.texcoord[0] >= 0.0);
+        assert!(vertex.texcoord[1] >= 0.0);
+        assert!(vertex.texcoord[0] <= 1.0);
+        assert!(vertex.texcoord[1] <= 1.0);
+    }
+}
+
+#[test]
+pub fn ensure_default_cylinder_has_ccw_triangles() {
+    let vertices = CylinderBuilder::new()
+        .build_vertices()
+        .expect("Failed to build vertices");
+    for chunk in vertices.chunks(3) {
+        let v0 = Vector3::<f32>::from(chunk[0].position);
+        let v1 = Vector3::<f32>::from(chunk[1].position);
+        let v2 = Vector3::<f32>::from(chunk[2].position);
+        let eyepos = v0 + Vector3::<f32>::from(chunk[0].normal);
+        let e0 = v1 - v0;
+        let e1 = v2 - v0;
+        let n = e0.cross(e1);
+        assert!(n.dot(v0 - eyepos) <= 0.0);
+        assert!(n.dot(v1 - eyepos) <= 0.0);
+        assert!(n.dot(v2 - eyepos) <= 0.0);
+    }
+}
+
+#[test]
+pub fn ensure_default_cylinder_has_faceted_normals() {
+    let vertices = CylinderBuilder::new()
+        .build_vertices()
+        .expect("Failed to build vertices");
+
+    for chunk in vertices.chunks(3) {
+        let v0 = Vector3::<f32>::from(chunk[0].position);
+        let v1 = Vector3::<f32>::from(chunk[1].position);
+        let v2 = Vector3::<f32>::from(chunk[2].position);
+        let n0 = Vector3::<f32>::from(chunk[0].normal);
+        let n1 = Vector3::<f32>::from(chunk[1].normal);
+        let n2 = Vector3::<f32>::from(chunk[2].normal);
+        let e0 = v1 - v0;
+        let e1 = v2 - v0;
+        let n = e0.cross(e1).normalize();
+        assert_ulps_eq!(n, n0, epsilon = 0.0001);
+        assert_ulps_eq!(n, n1, epsilon = 0.0001);
+        assert_ulps_eq!(n, n2, epsilon = 0.0001);
+    }
+}
+
+#[test]
+pub fn ensure_open_ended_cylinder_has_no_caps() {
+    let builder = CylinderBuilder::new().with_divisions(8, 3).open_ended();
+    let vertices = builder.build_vertices().expect("Failed to build vertices");
+    assert_eq!(builder.num_caps(), 0);
+    assert_eq!(vertices.len(), 8 * 3 * 6);
+    for vertex in &vertices {
+        assert!(abs_diff_eq!(vertex.normal[1], 0.0));
+    }
+}
+
+#[test]
+pub fn ensure_cylinder_with_too_few_divisions_fails() {
+    match CylinderBuilder::new().with_divisions(2, 1).build_vertices() {
+        Err(ShapeCreationError::NotEnoughDivisionsInU) => {}
+        _ => panic!("Expected NotEnoughDivisionsInU"),
+    }
+    match CylinderBuilder::new().with_divisions(3, 0).build_vertices() {
+        Err(ShapeCreationError::NotEnoughDivisionsInV) => {}
+        _ => panic!("Expected NotEnoughDivisionsInV"),
+    }
+}
+
+#[test]
+pub fn ensure_smooth_cylinder_has_radial_side_normals() {
+    let vertices = CylinderBuilder::new()
+        .with_shading(Shading::Smooth)
+        .open_ended()
+        .build_vertices()
+        .expect("Failed to build vertices");
+    for vertex in &vertices {
+        let radial = Vector3::<f32>::new(vertex.position[0], 0.0, vertex.position[2]);
+        assert_ulps_eq!(
+            Vector3::<f32>::from(vertex.normal),
+            radial,
+            epsilon = 0.0001
+        );
+    }
+}
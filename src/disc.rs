@@ -0,0 +1,316 @@
+//! A module for constructing flat disc, annulus (ring), and sector shapes.
+
+extern crate cgmath;
+#[cfg(feature = "glium")]
+extern crate glium;
+
+use self::cgmath::*;
+use crate::errors::ShapeCreationError;
+use crate::index;
+#[cfg(feature = "glium")]
+use crate::mesh::Mesh;
+use crate::shape::{ShapeBuilder, ShapeOptions};
+use crate::vertex::Vertex;
+
+use std::f32;
+
+/// A polygonal `Disc` object.
+///
+/// This object is constructed using a `DiscBuilder` object.
+#[cfg(feature = "glium")]
+pub type Disc = Mesh;
+
+/// Responsible for building and returning a `Disc` object.
+///
+/// By default, the resultant polygon is a filled circle with a radius of 1,
+/// with its centre at the origin, and aligned to face the negative Z-axis (in
+/// the same way as a `QuadBuilder`). Specifying a non-zero inner radius
+/// produces an annulus (a flat ring), and specifying start and end angles
+/// produces a sector (pie slice) of the disc or annulus. The default position,
+/// size, and alignment can be overridden using the transformation methods on
+/// this object.
+///
+/// The resultant geometry is constructed to suit OpenGL defaults - assuming
+/// a right-handed coordinate system, front-facing polygons are defined in
+/// counter-clock-wise order. Vertex normals point in the direction of the
+/// face. Vertex texture coordinates define a planar-projection on the face,
+/// such that the bounding square of the disc maps to the 0 to 1 range.
+pub struct DiscBuilder {
+    options: ShapeOptions,
+    segments: usize,
+    inner_radius: f32,
+    start_angle: f32,
+    end_angle: f32,
+}
+
+impl Default for DiscBuilder {
+    fn default() -> DiscBuilder {
+        DiscBuilder {
+            options: ShapeOptions::default(),
+            segments: 32,
+            inner_radius: 0.0,
+            start_angle: 0.0,
+            end_angle: 2.0 * f32::consts::PI,
+        }
+    }
+}
+
+impl DiscBuilder {
+    /// Create a new `DiscBuilder` object.
+    pub fn new() -> DiscBuilder {
+        Default::default()
+    }
+
+    /// Specify the number of segments to divide the disc into. By default,
+    /// the builder will use 32 segments.
+    pub fn with_divisions(mut self, segments: usize) -> Self {
+        self.segments = segments;
+        self
+    }
+
+    /// Specify the radius of the hole in the centre of the disc, relative to
+    /// its outer radius of 1. A radius of 0 (the default) produces a filled
+    /// disc, and a radius between 0 and 1 produces an annulus.
+    pub fn with_inner_radius(mut self, radius: f32) -> Self {
+        self.inner_radius = radius;
+        self
+    }
+
+    /// Specify the angles (in radians) at which the disc starts and ends,
+    /// measured counter-clock-wise from the positive X-axis about the Z-axis.
+    /// By default, the disc spans a full revolution (0 to 2 * PI), and larger
+    /// spans are clamped to it. The shape fails to build if either angle is not
+    /// finite, or if the end angle is not greater than the start angle.
+    pub fn with_angles(mut self, start: f32, end: f32) -> Self {
+        self.start_angle = start;
+        self.end_angle = end;
+        self
+    }
+
+    /// Returns the total number of vertices in each segment. This will return 3
+    /// for a filled disc, and 6 for an annulus.
+    pub fn num_vertices_per_segment(&self) -> usize {
+        if self.inner_radius == 0.0 {
+            3
+        } else {
+            6
+        }
+    }
+}
+
+impl ShapeBuilder for DiscBuilder {
+    fn options(&self) -> &ShapeOptions {
+        &self.options
+    }
+
+    fn options_mut(&mut self) -> &mut ShapeOptions {
+        &mut self.options
+    }
+
+    fn primitives(&self) -> index::PrimitiveType {
+        index::PrimitiveType::TrianglesList
+    }
+
+    fn build_unindexed_vertices(&self) -> Result<Vec<Vertex>, ShapeCreationError> {
+        // Ensure the angles span a positive range, that there are enough segments
+        // to produce valid geometry, and that the hole (if any) fits inside the
+        // disc.
+        let angles = [self.start_angle, self.end_angle];
+        if !angles.iter().all(|x| x.is_finite()) || self.end_angle <= self.start_angle {
+            return Err(ShapeCreationError::InvalidAngle);
+        }
+
+        let sweep = (self.end_angle - self.start_angle).min(2.0 * f32::consts::PI);
+        let full_circle = sweep >= 2.0 * f32::consts::PI;
+        if self.segments < 1 || (full_circle && self.segments < 3) {
+            return Err(ShapeCreationError::NotEnoughDivisionsInU);
+        }
+
+        if self.inner_radius.is_nan() || self.inner_radius < 0.0 || self.inner_radius >= 1.0 {
+            return Err(ShapeCreationError::InvalidRadius);
+        }
+
+        // Build lookup tables. When the disc is closed, the last segment wraps
+        // around to exactly coincide with the first.
+        let angle = sweep / self.segments as f32;
+        let wrap = if full_circle {
+            self.segments
+        } else {
+            self.segments + 1
+        };
+
+        let tab = (0..=self.segments)
+            .map(|x| {
+                let theta = self.start_angle + ((x % wrap) as f32) * angle;
+                Vector2::<f32>::new(theta.cos(), theta.sin())
+            })
+            .collect::<Vec<Vector2<f32>>>();
+
+        // Compute the normal transformation matrix.
+        let normal_matrix = self.options.normal_matrix();
+
+        // Build vertex array.
+        let normal = (normal_matrix * Vector3::<f32>::new(0.0, 0.0, -1.0)).normalize();
+        let total_num_verts = self.num_unindexed_vertices();
+        let mut vertices = Vec::<Vertex>::with_capacity(total_num_verts);
+
+        let mut emit = |vpos: Vector2<f32>| {
+            let position = Vector4::<f32>::new(vpos.x, vpos.y, 0.0, 1.0);
+            vertices.push(Vertex {
+                position: Point3::<f32>::from_homogeneous(self.options.matrix * position).into(),
+                normal: normal.into(),
+                texcoord: [vpos.x * 0.5 + 0.5, vpos.y * 0.5 + 0.5],
+            });
+        };
+
+        for segment in 0..self.segments {
+            let outer = [tab[segment], tab[segment + 1]];
+            if self.inner_radius == 0.0 {
+                emit(Vector2::<f32>::zero());
+                emit(outer[1]);
+                emit(outer[0]);
+            } else {
+                let inner = [outer[0] * self.inner_radius, outer[1] * self.inner_radius];
+                emit(inner[0]);
+                emit(outer[1]);
+                emit(outer[0]);
+                emit(inner[0]);
+                emit(inner[1]);
+                emit(outer[1]);
+            }
+        }
+
+        assert!(vertices.len() == total_num_verts);
+        Ok(vertices)
+    }
+
+    fn num_unindexed_vertices(&self) -> usize {
+        self.num_vertices_per_segment() * self.segments
+    }
+}
+
+#[test]
+pub fn ensure_default_disc_has_unit_radius() {
+    let vertices = DiscBuilder::new()
+        .build_vertices()
+        .expect("Failed to build vertices");
+    for vertex in &vertices {
+        let pos = Vector3::<f32>::from(vertex.position);
+        assert_eq!(pos.z, 0.0);
+        assert!(pos.magnitude() <= 1.0 + 1e-6);
+    }
+}
+
+#[test]
+pub fn ensure_default_disc_has_centroid_at_origin() {
+    let vertices = DiscBuilder::new()
+        .build_vertices()
+        .expect("Failed to build vertices");
+    let mut sum = Vector3::<f32>::zero();
+    for vertex in &vertices {
+        sum += Vector3::<f32>::from(vertex.position);
+    }
+    assert_ulps_eq!(sum, Vector3::<f32>::zero(), epsilon = 0.0001);
+}
+
+#[test]
+pub fn ensure_default_disc_has_ccw_triangles() {
+    for builder in [
+        DiscBuilder::new(),
+        DiscBuilder::new().with_inner_radius(0.5),
+    ] {
+        let vertices = builder.build_vertices().expect("Failed to build vertices");
+        for chunk in vertices.chunks(3) {
+            let v0 = Vector3::<f32>::from(chunk[0].position);
+            let v1 = Vector3::<f32>::from(chunk[1].position);
+            let v2 = Vector3::<f32>::from(chunk[2].position);
+            let eyepos = v0 + Vector3::<f32>::from(chunk[0].normal);
+            let e0 = v1 - v0;
+            let e1 = v2 - v0;
+            let n = e0.cross(e1);
+            assert!(n.dot(v0 - eyepos) <= 0.0);
+            assert!(n.dot(v1 - eyepos) <= 0.0);
+            assert!(n.dot(v2 - eyepos) <= 0.0);
+        }
+    }
+}
+
+#[test]
+pub fn ensure_default_disc_faces_the_same_way_as_a_quad() {
+    let quad = crate::quad::QuadBuilder::new()
+        .build_vertices()
+        .expect("Failed to build vertices");
+    let vertices = DiscBuilder::new()
+        .build_vertices()
+        .expect("Failed to build vertices");
+    for vertex in &vertices {
+        assert_eq!(vertex.normal, quad[0].normal);
+    }
+}
+
+#[test]
+pub fn ensure_disc_uvs_are_planar_and_in_unit_range() {
+    let vertices = DiscBuilder::new()
+        .with_inner_radius(0.25)
+        .build_vertices()
+        .expect("Failed to build vertices");
+    for vertex in &vertices {
+        assert!(vertex.texcoord[0] >= 0.0 && vertex.texcoord[0] <= 1.0);
+        assert!(vertex.texcoord[1] >= 0.0 && vertex.texcoord[1] <= 1.0);
+        assert_ulps_eq!(vertex.texcoord[0], vertex.position[0] * 0.5 + 0.5);
+        assert_ulps_eq!(vertex.texcoord[1], vertex.position[1] * 0.5 + 0.5);
+    }
+}
+
+#[test]
+pub fn ensure_annulus_has_hole_of_inner_radius() {
+    let builder = DiscBuilder::new().with_divisions(16).with_inner_radius(0.5);
+    let vertices = builder.build_vertices().expect("Failed to build vertices");
+    assert_eq!(vertices.len(), 16 * 6);
+    for vertex in &vertices {
+        let radius = Vector3::<f32>::from(vertex.position).magnitude();
+        assert!(
+            abs_diff_eq!(radius, 0.5, epsilon = 1e-6) || abs_diff_eq!(radius, 1.0, epsilon = 1e-6)
+        );
+    }
+}
+
+#[test]
+pub fn ensure_sector_is_limited_to_its_angles() {
+    let vertices = DiscBuilder::new()
+        .with_divisions(4)
+        .with_angles(0.0, f32::consts::PI / 2.0)
+        .build_vertices()
+        .expect("Failed to build vertices");
+    assert_eq!(vertices.len(), 4 * 3);
+    for vertex in &vertices {
+        assert!(vertex.position[0] >= -1e-6);
+        assert!(vertex.position[1] >= -1e-6);
+    }
+}
+
+#[test]
+pub fn ensure_disc_with_invalid_parameters_fails() {
+    match DiscBuilder::new().with_divisions(2).build_vertices() {
+        Err(ShapeCreationError::NotEnoughDivisionsInU) => {}
+        _ => panic!("Expected NotEnoughDivisionsInU"),
+    }
+    for radius in &[1.0, -0.5, f32::NAN] {
+        match DiscBuilder::new()
+            .with_inner_radius(*radius)
+            .build_vertices()
+        {
+            Err(ShapeCreationError::InvalidRadius) => {}
+            _ => panic!("Expected InvalidRadius"),
+        }
+    }
+    for (start, end) in &[(1.0, 1.0), (1.0, 0.5), (0.0, f32::NAN), (f32::NAN, 1.0)] {
+        match DiscBuilder::new()
+            .with_angles(*start, *end)
+            .build_vertices()
+        {
+            Err(ShapeCreationError::InvalidAngle) => {}
+            _ => panic!("Expected InvalidAngle"),
+        }
+    }
+}
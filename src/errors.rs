@@ -1,16 +1,23 @@
 //! A module containing the error structures for this crate.
 extern crate core;
+#[cfg(feature = "glium")]
 extern crate glium;
 extern crate std;
 
+use crate::index::PrimitiveType;
 use std::error::Error;
 
 /// The error object that is returned when a shape fails to build.
 #[derive(Debug, Copy, Clone)]
 pub enum ShapeCreationError {
     /// The shape failed to build because vertex buffer could not be created.
+    #[cfg(feature = "glium")]
     VertexBufferCreationError(glium::vertex::BufferCreationError),
 
+    /// The shape failed to build because index buffer could not be created.
+    #[cfg(feature = "glium")]
+    IndexBufferCreationError(glium::index::BufferCreationError),
+
     /// The shape failed to build because the number of divisions in the u axis
     /// is too small.
     NotEnoughDivisionsInU,
@@ -18,33 +25,143 @@ pub enum ShapeCreationError {
     /// The shape failed to build because the number of divisions in the v axis
     /// is too small.
     NotEnoughDivisionsInV,
+
+    /// The shape failed to build because one of its radii is negative, or
+    /// the combination of radii would produce degenerate geometry.
+    InvalidRadius,
+
+    /// The shape failed to build because its height is zero or negative.
+    InvalidHeight,
+
+    /// The shape failed to build because an angle (e.g. the sweep of a partial
+    /// torus, or the angles of a disc sector) is not a number, or does not span a
+    /// positive range.
+    InvalidAngle,
+
+    /// The shape failed to build because cubemap texture coordinates were requested
+    /// along with tangents or a colour, which no built-in vertex type can hold
+    /// together (use `build_as` with a custom vertex type instead).
+    UnsupportedCubemapAttributes,
+
+    /// The shape failed to build because it was subdivided too many times (such
+    /// that its vertices could no longer be counted or indexed).
+    TooManySubdivisions,
 }
 
 impl std::error::Error for ShapeCreationError {
-    fn description(&self) -> &str {
-        match &self {
-            ShapeCreationError::VertexBufferCreationError(ref err) => err.description(),
-            ShapeCreationError::NotEnoughDivisionsInU => "Not enough divisions in the u axis",
-            ShapeCreationError::NotEnoughDivisionsInV => "Not enough divisions in the v axis",
-        }
-    }
-
-    fn cause(&self) -> Option<&dyn Error> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
         match &self {
+            #[cfg(feature = "glium")]
             ShapeCreationError::VertexBufferCreationError(ref error) => Some(error),
+            #[cfg(feature = "glium")]
+            ShapeCreationError::IndexBufferCreationError(ref error) => Some(error),
             _ => None,
         }
     }
 }
 
+#[cfg(feature = "glium")]
 impl From<glium::vertex::BufferCreationError> for ShapeCreationError {
     fn from(error: glium::vertex::BufferCreationError) -> Self {
         ShapeCreationError::VertexBufferCreationError(error)
     }
 }
 
+#[cfg(feature = "glium")]
+impl From<glium::index::BufferCreationError> for ShapeCreationError {
+    fn from(error: glium::index::BufferCreationError) -> Self {
+        ShapeCreationError::IndexBufferCreationError(error)
+    }
+}
+
 impl core::fmt::Display for ShapeCreationError {
     fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
-        write!(fmt, "{}", self.description())
+        match &self {
+            #[cfg(feature = "glium")]
+            ShapeCreationError::VertexBufferCreationError(ref err) => write!(fmt, "{}", err),
+            #[cfg(feature = "glium")]
+            ShapeCreationError::IndexBufferCreationError(ref err) => write!(fmt, "{}", err),
+            ShapeCreationError::NotEnoughDivisionsInU => {
+                write!(fmt, "Not enough divisions in the u axis")
+            }
+            ShapeCreationError::NotEnoughDivisionsInV => {
+                write!(fmt, "Not enough divisions in the v axis")
+            }
+            ShapeCreationError::InvalidRadius => write!(fmt, "Invalid radius"),
+            ShapeCreationError::InvalidHeight => write!(fmt, "Invalid height"),
+            ShapeCreationError::InvalidAngle => write!(fmt, "Invalid angle"),
+            ShapeCreationError::TooManySubdivisions => write!(fmt, "Too many subdivisions"),
+            ShapeCreationError::UnsupportedCubemapAttributes => write!(
+                fmt,
+                "Cubemap texture coordinates cannot be combined with tangents or colours"
+            ),
+        }
+    }
+}
+
+/// The error object that is returned when a shape fails to export.
+#[derive(Debug)]
+pub enum ExportError {
+    /// The shape failed to export because it could not be written to the stream.
+    Io(std::io::Error),
+
+    /// The shape failed to export because the file format does not support its
+    /// type of primitive (e.g. when exporting the lines of `Axes` to STL).
+    UnsupportedPrimitive(PrimitiveType),
+
+    /// The shape failed to export because the named per-vertex property does not
+    /// hold exactly one value for each vertex.
+    PropertyLengthMismatch(String),
+
+    /// The shape failed to export because the named per-vertex property has the
+    /// same name as another property.
+    DuplicateProperty(String),
+
+    /// The shape failed to export because a per-vertex property has an empty name.
+    EmptyPropertyName,
+
+    /// The shape failed to export because it has no vertices (or no indices), or
+    /// there were no shapes to export, which the file format does not allow.
+    EmptyShape,
+
+    /// The shape failed to export because the vertex at the given index holds a
+    /// non-finite (infinite or NaN) value, which the file format cannot represent.
+    NonFiniteVertex(usize),
+}
+
+impl std::error::Error for ExportError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match &self {
+            ExportError::Io(ref error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for ExportError {
+    fn from(error: std::io::Error) -> Self {
+        ExportError::Io(error)
+    }
+}
+
+impl core::fmt::Display for ExportError {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match &self {
+            ExportError::Io(ref err) => write!(fmt, "{}", err),
+            ExportError::UnsupportedPrimitive(ref primitives) => {
+                write!(fmt, "Unsupported primitive type {:?}", primitives)
+            }
+            ExportError::PropertyLengthMismatch(ref name) => {
+                write!(fmt, "Property {} does not have one value per vertex", name)
+            }
+            ExportError::DuplicateProperty(ref name) => {
+                write!(fmt, "Property {} is defined more than once", name)
+            }
+            ExportError::EmptyPropertyName => write!(fmt, "Property has an empty name"),
+            ExportError::EmptyShape => write!(fmt, "No shapes, vertices, or indices to export"),
+            ExportError::NonFiniteVertex(ref index) => {
+                write!(fmt, "Vertex {} holds a non-finite value", index)
+            }
+        }
     }
 }
@@ -0,0 +1,415 @@
+//! A module for exporting shapes to the glTF 2.0 file format.
+
+use crate::errors::ExportError;
+use crate::index::PrimitiveType;
+use crate::vertex::Vertex;
+
+use std::io::Write;
+
+/// The `target` of buffer views that hold vertex attributes.
+const ARRAY_BUFFER: u32 = 34962;
+
+/// The `target` of buffer views that hold indices.
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+/// The `componentType` of accessors that hold 32-bit floats.
+const FLOAT: u32 = 5126;
+
+/// The `componentType` of accessors that hold 32-bit unsigned integers.
+const UNSIGNED_INT: u32 = 5125;
+
+/// Returns the glTF primitive `mode` that corresponds to the given type of
+/// primitive, or an error if glTF does not support it.
+fn mode(primitives: PrimitiveType) -> Result<u32, ExportError> {
+    #[allow(unreachable_patterns)]
+    match primitives {
+        PrimitiveType::Points => Ok(0),
+        PrimitiveType::LinesList => Ok(1),
+        PrimitiveType::LineLoop => Ok(2),
+        PrimitiveType::LineStrip => Ok(3),
+        PrimitiveType::TrianglesList => Ok(4),
+        PrimitiveType::TriangleStrip => Ok(5),
+        PrimitiveType::TriangleFan => Ok(6),
+        _ => Err(ExportError::UnsupportedPrimitive(primitives)),
+    }
+}
+
+/// Returns the given string as a JSON string literal.
+fn json_string(value: &str) -> String {
+    let mut result = String::with_capacity(value.len() + 2);
+    result.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            c if (c as u32) < 0x20 => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+/// Returns the given values as a JSON array.
+fn json_array<T: std::fmt::Display>(values: &[T]) -> String {
+    let values = values.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+    format!("[{}]", values.join(","))
+}
+
+/// Collects shapes into a single glTF 2.0 scene, which can then be written as a
+/// `.gltf` file alongside a `.bin` file, or as a single binary `.glb` file.
+///
+/// Each shape is written as a separate node (and mesh) in the scene, holding the
+/// positions, normals, and texture coordinates of every vertex, along with its
+/// indices (if any). Shapes made up of lines (e.g. `Axes`) are written as `LINES`
+/// primitives, triangle strips (e.g. `Quad`) as `TRIANGLE_STRIP` primitives, and
+/// triangle lists as `TRIANGLES` primitives.
+///
+/// As glTF texture coordinates are measured from the top-left of an image, the v
+/// texture coordinate of each vertex is flipped (i.e. written as `1 - v`).
+#[derive(Default)]
+pub struct GltfWriter {
+    buffer: Vec<u8>,
+    buffer_views: Vec<String>,
+    accessors: Vec<String>,
+    meshes: Vec<String>,
+    nodes: Vec<String>,
+}
+
+impl GltfWriter {
+    /// Create a new `GltfWriter` object holding an empty scene.
+    pub fn new() -> GltfWriter {
+        GltfWriter::default()
+    }
+
+    /// Add a shape to the scene, given its vertices, its indices (if any), and the
+    /// type of primitive they make up (e.g. as returned by the `build_vertices`,
+    /// `build_indices` and `primitives` methods of a builder). When a name is
+    /// given, it is used to name both the node and mesh of the shape.
+    ///
+    /// As glTF requires every accessor to hold at least one (finite) value, an
+    /// error is returned if the shape has no vertices (or an empty list of
+    /// indices), or if any of its vertices hold a non-finite value.
+    pub fn add_shape(
+        &mut self,
+        name: Option<&str>,
+        vertices: &[Vertex],
+        indices: Option<&[u32]>,
+        primitives: PrimitiveType,
+    ) -> Result<(), ExportError> {
+        let mode = mode(primitives)?;
+        if vertices.is_empty() || indices.is_some_and(|x| x.is_empty()) {
+            return Err(ExportError::EmptyShape);
+        }
+        let finite = |values: &[f32]| values.iter().all(|x| x.is_finite());
+        if let Some(index) = vertices
+            .iter()
+            .position(|x| !finite(&x.position) || !finite(&x.normal) || !finite(&x.texcoord))
+        {
+            return Err(ExportError::NonFiniteVertex(index));
+        }
+        let positions = vertices.iter().map(|x| x.position).collect::<Vec<_>>();
+        let normals = vertices.iter().map(|x| x.normal).collect::<Vec<_>>();
+        let texcoords = vertices
+            .iter()
+            .map(|x| [x.texcoord[0], 1.0 - x.texcoord[1]])
+            .collect::<Vec<_>>();
+
+        let position = self.add_attribute(&positions, "VEC3");
+        let normal = self.add_attribute(&normals, "VEC3");
+        let texcoord = self.add_attribute(&texcoords, "VEC2");
+        let mut primitive = format!(
+            "{{\"attributes\":{{\"POSITION\":{},\"NORMAL\":{},\"TEXCOORD_0\":{}}},\"mode\":{}",
+            position, normal, texcoord, mode
+        );
+        if let Some(indices) = indices {
+            let accessor = self.add_indices(indices);
+            primitive.push_str(&format!(",\"indices\":{}", accessor));
+        }
+        primitive.push('}');
+
+        let name = name
+            .map(|x| format!(",\"name\":{}", json_string(x)))
+            .unwrap_or_default();
+        self.meshes
+            .push(format!("{{\"primitives\":[{}]{}}}", primitive, name));
+        self.nodes
+            .push(format!("{{\"mesh\":{}{}}}", self.meshes.len() - 1, name));
+        Ok(())
+    }
+
+    /// Append a vertex attribute to the buffer, returning the index of its
+    /// accessor.
+    fn add_attribute<T: AsRef<[f32]>>(&mut self, values: &[T], kind: &str) -> usize {
+        let components = values.first().map_or(0, |x| x.as_ref().len());
+        let mut min = vec![f32::INFINITY; components];
+        let mut max = vec![f32::NEG_INFINITY; components];
+        let offset = self.buffer.len();
+        for value in values {
+            for (axis, component) in value.as_ref().iter().enumerate() {
+                min[axis] = min[axis].min(*component);
+                max[axis] = max[axis].max(*component);
+                self.buffer.extend_from_slice(&component.to_le_bytes());
+            }
+        }
+        let view = self.add_buffer_view(offset, ARRAY_BUFFER);
+        self.add_accessor(view, FLOAT, values.len(), kind, &min, &max)
+    }
+
+    /// Append a list of indices to the buffer, returning the index of its accessor.
+    fn add_indices(&mut self, indices: &[u32]) -> usize {
+        let offset = self.buffer.len();
+        for index in indices {
+            self.buffer.extend_from_slice(&index.to_le_bytes());
+        }
+        let min = indices.iter().cloned().min().unwrap_or(0);
+        let max = indices.iter().cloned().max().unwrap_or(0);
+        let view = self.add_buffer_view(offset, ELEMENT_ARRAY_BUFFER);
+        self.add_accessor(view, UNSIGNED_INT, indices.len(), "SCALAR", &[min], &[max])
+    }
+
+    /// Add a buffer view from the given offset to the end of the buffer, returning
+    /// its index. As every value is four bytes long, views are always aligned.
+    fn add_buffer_view(&mut self, offset: usize, target: u32) -> usize {
+        self.buffer_views.push(format!(
+            "{{\"buffer\":0,\"byteOffset\":{},\"byteLength\":{},\"target\":{}}}",
+            offset,
+            self.buffer.len() - offset,
+            target
+        ));
+        self.buffer_views.len() - 1
+    }
+
+    /// Add an accessor for the given buffer view, returning its index.
+    fn add_accessor<T: std::fmt::Display>(
+        &mut self,
+        view: usize,
+        component_type: u32,
+        count: usize,
+        kind: &str,
+        min: &[T],
+        max: &[T],
+    ) -> usize {
+        self.accessors.push(format!(
+            "{{\"bufferView\":{},\"componentType\":{},\"count\":{},\"type\":\"{}\",\"min\":{},\"max\":{}}}",
+            view,
+            component_type,
+            count,
+            kind,
+            json_array(min),
+            json_array(max)
+        ));
+        self.accessors.len() - 1
+    }
+
+    /// Returns the JSON describing the scene, referencing the buffer by the given
+    /// URI (or by no URI at all, when the buffer is embedded in a `.glb` file).
+    fn json(&self, uri: Option<&str>) -> String {
+        let uri = uri
+            .map(|x| format!(",\"uri\":{}", json_string(x)))
+            .unwrap_or_default();
+        let nodes = (0..self.nodes.len()).collect::<Vec<_>>();
+        format!(
+            "{{\"asset\":{{\"version\":\"2.0\",\"generator\":\"glium_shapes\"}},\
+             \"scene\":0,\"scenes\":[{{\"nodes\":{}}}],\"nodes\":[{}],\"meshes\":[{}],\
+             \"accessors\":[{}],\"bufferViews\":[{}],\"buffers\":[{{\"byteLength\":{}{}}}]}}",
+            json_array(&nodes),
+            self.nodes.join(","),
+            self.meshes.join(","),
+            self.accessors.join(","),
+            self.buffer_views.join(","),
+            self.buffer.len(),
+            uri
+        )
+    }
+
+    /// Write the scene as a `.gltf` file to the first stream, and its buffer to the
+    /// second stream, which the `.gltf` file references using the given URI (e.g.
+    /// the name of the `.bin` file, relative to the `.gltf` file). An error is
+    /// returned if no shapes have been added, as glTF does not allow empty scenes.
+    pub fn write_gltf<W: Write, B: Write>(
+        &self,
+        mut gltf: W,
+        mut bin: B,
+        bin_uri: &str,
+    ) -> Result<(), ExportError> {
+        if self.nodes.is_empty() {
+            return Err(ExportError::EmptyShape);
+        }
+        gltf.write_all(self.json(Some(bin_uri)).as_bytes())?;
+        bin.write_all(&self.buffer)?;
+        Ok(())
+    }
+
+    /// Write the scene and its buffer to the given stream as a single `.glb` file,
+    /// in the same manner as `write_gltf`.
+    pub fn write_glb<W: Write>(&self, mut writer: W) -> Result<(), ExportError> {
+        if self.nodes.is_empty() {
+            return Err(ExportError::EmptyShape);
+        }
+        // Each chunk must be padded to a multiple of four bytes, using spaces for
+        // the JSON chunk, and zeros for the binary chunk.
+        let mut json = self.json(None).into_bytes();
+        json.resize(json.len().div_ceil(4) * 4, b' ');
+        let mut bin = self.buffer.clone();
+        bin.resize(bin.len().div_ceil(4) * 4, 0);
+
+        let length = 12 + 8 + json.len() + 8 + bin.len();
+        writer.write_all(b"glTF")?;
+        writer.write_all(&2u32.to_le_bytes())?;
+        writer.write_all(&(length as u32).to_le_bytes())?;
+        writer.write_all(&(json.len() as u32).to_le_bytes())?;
+        writer.write_all(b"JSON")?;
+        writer.write_all(&json)?;
+        writer.write_all(&(bin.len() as u32).to_le_bytes())?;
+        writer.write_all(b"BIN\0")?;
+        writer.write_all(&bin)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+use crate::shape::ShapeBuilder;
+
+#[cfg(test)]
+fn add_builder<B: ShapeBuilder>(writer: &mut GltfWriter, name: &str, builder: B) {
+    let vertices = builder.build_vertices().expect("Failed to build vertices");
+    let indices = builder.build_indices().expect("Failed to build indices");
+    writer
+        .add_shape(Some(name), &vertices, Some(&indices), builder.primitives())
+        .expect("Failed to add shape");
+}
+
+#[test]
+pub fn ensure_gltf_primitive_modes_match_shapes() {
+    let mut writer = GltfWriter::new();
+    add_builder(&mut writer, "axes", crate::axes::AxesBuilder::new());
+    add_builder(&mut writer, "quad", crate::quad::QuadBuilder::new());
+    add_builder(&mut writer, "cuboid", crate::cuboid::CuboidBuilder::new());
+    let json = writer.json(None);
+    assert!(json.contains("\"mode\":1,\"indices\":3}],\"name\":\"axes\""));
+    assert!(json.contains("\"mode\":5,\"indices\":7}],\"name\":\"quad\""));
+    assert!(json.contains("\"mode\":4,\"indices\":11}],\"name\":\"cuboid\""));
+    assert!(json.contains("\"scenes\":[{\"nodes\":[0,1,2]}]"));
+}
+
+#[test]
+pub fn ensure_gltf_position_bounds_match_vertices() {
+    let mut writer = GltfWriter::new();
+    add_builder(
+        &mut writer,
+        "cuboid",
+        crate::cuboid::CuboidBuilder::new()
+            .scale(2.0, 3.0, 4.0)
+            .translate(1.0, 0.0, 0.0),
+    );
+    let json = writer.json(None);
+    assert!(json.contains("\"count\":36,\"type\":\"VEC3\",\"min\":[0,-1.5,-2],\"max\":[2,1.5,2]"));
+    assert!(json.contains("\"count\":36,\"type\":\"SCALAR\",\"min\":[0],\"max\":[35]"));
+}
+
+#[test]
+pub fn ensure_glb_chunks_are_aligned() {
+    let mut writer = GltfWriter::new();
+    add_builder(
+        &mut writer,
+        "sphere",
+        crate::sphere::SphereBuilder::new().indexed(),
+    );
+    let mut glb = Vec::<u8>::new();
+    writer.write_glb(&mut glb).expect("Failed to write GLB");
+
+    let read_u32 = |offset: usize| {
+        u32::from_le_bytes([
+            glb[offset],
+            glb[offset + 1],
+            glb[offset + 2],
+            glb[offset + 3],
+        ]) as usize
+    };
+    assert_eq!(&glb[..4], b"glTF");
+    assert_eq!(read_u32(4), 2);
+    assert_eq!(read_u32(8), glb.len());
+    let json_length = read_u32(12);
+    assert_eq!(&glb[16..20], b"JSON");
+    assert_eq!(json_length % 4, 0);
+    let bin = 20 + json_length;
+    assert_eq!(&glb[bin + 4..bin + 8], b"BIN\0");
+    assert_eq!(&glb[bin + 8..], &writer.buffer[..]);
+    assert_eq!(glb.len(), bin + 8 + read_u32(bin));
+}
+
+#[test]
+pub fn ensure_gltf_buffer_holds_vertices() {
+    let builder = crate::quad::QuadBuilder::new();
+    let vertices = builder.build_vertices().expect("Failed to build vertices");
+    let mut writer = GltfWriter::new();
+    writer
+        .add_shape(None, &vertices, None, builder.primitives())
+        .expect("Failed to add shape");
+    let (mut gltf, mut bin) = (Vec::<u8>::new(), Vec::<u8>::new());
+    writer
+        .write_gltf(&mut gltf, &mut bin, "quad.bin")
+        .expect("Failed to write glTF");
+
+    let gltf = String::from_utf8(gltf).expect("Invalid UTF-8");
+    assert!(gltf.contains("\"buffers\":[{\"byteLength\":128,\"uri\":\"quad.bin\"}]"));
+    let floats = bin
+        .chunks(4)
+        .map(|x| f32::from_le_bytes([x[0], x[1], x[2], x[3]]))
+        .collect::<Vec<f32>>();
+    for (index, vertex) in vertices.iter().enumerate() {
+        assert_eq!(&floats[index * 3..index * 3 + 3], &vertex.position[..]);
+        assert_eq!(
+            &floats[12 + index * 3..12 + index * 3 + 3],
+            &vertex.normal[..]
+        );
+        assert_eq!(floats[24 + index * 2], vertex.texcoord[0]);
+        assert_eq!(floats[24 + index * 2 + 1], 1.0 - vertex.texcoord[1]);
+    }
+}
+
+#[test]
+pub fn ensure_gltf_rejects_invalid_vertices() {
+    let builder = crate::quad::QuadBuilder::new();
+    let mut vertices = builder.build_vertices().expect("Failed to build vertices");
+    let mut writer = GltfWriter::new();
+    match writer.add_shape(None, &[], None, builder.primitives()) {
+        Err(ExportError::EmptyShape) => {}
+        _ => panic!("Expected an empty shape error"),
+    }
+    match writer.add_shape(None, &vertices, Some(&[]), builder.primitives()) {
+        Err(ExportError::EmptyShape) => {}
+        _ => panic!("Expected an empty shape error"),
+    }
+    vertices[2].normal[1] = f32::NAN;
+    match writer.add_shape(None, &vertices, None, builder.primitives()) {
+        Err(ExportError::NonFiniteVertex(2)) => {}
+        _ => panic!("Expected a non-finite vertex error"),
+    }
+    vertices[2].normal[1] = 0.0;
+    vertices[1].position[0] = f32::INFINITY;
+    match writer.add_shape(None, &vertices, None, builder.primitives()) {
+        Err(ExportError::NonFiniteVertex(1)) => {}
+        _ => panic!("Expected a non-finite vertex error"),
+    }
+    assert!(writer.buffer.is_empty());
+    assert!(writer.accessors.is_empty());
+    assert!(writer.meshes.is_empty());
+}
+
+#[test]
+pub fn ensure_empty_gltf_scenes_are_rejected() {
+    let writer = GltfWriter::new();
+    let (mut gltf, mut bin) = (Vec::<u8>::new(), Vec::<u8>::new());
+    match writer.write_gltf(&mut gltf, &mut bin, "empty.bin") {
+        Err(ExportError::EmptyShape) => {}
+        _ => panic!("Expected an empty shape error"),
+    }
+    match writer.write_glb(&mut gltf) {
+        Err(ExportError::EmptyShape) => {}
+        _ => panic!("Expected an empty shape error"),
+    }
+    assert!(gltf.is_empty());
+    assert!(bin.is_empty());
+}
@@ -0,0 +1,63 @@
+//! A module for exporting shapes to common 3D file formats, such that they can be
+//! inspected or processed using other tools (e.g. Blender or MeshLab).
+//!
+//! Each exporter accepts the output of a builder's `build_vertices` method, along
+//! with the indices returned by its `build_indices` method (if any), and the type
+//! of primitive returned by its `primitives` method.
+
+pub mod gltf;
+pub mod obj;
+pub mod ply;
+pub mod stl;
+
+use crate::index::PrimitiveType;
+
+/// Returns the indices of a shape, or indices that simply count up from zero
+/// when the shape has none.
+pub(crate) fn resolve_indices(num_vertices: usize, indices: Option<&[u32]>) -> Vec<u32> {
+    match indices {
+        Some(indices) => indices.to_vec(),
+        None => (0..num_vertices as u32).collect(),
+    }
+}
+
+/// Returns the triangles made up by the given indices, with counter-clock-wise
+/// winding. Triangle strips are expanded into separate triangles, where every
+/// other triangle has its first two corners swapped to preserve its winding (as
+/// OpenGL does). Other types of primitive produce no triangles.
+pub(crate) fn triangles(indices: &[u32], primitives: PrimitiveType) -> Vec<[u32; 3]> {
+    match primitives {
+        PrimitiveType::TrianglesList => indices
+            .chunks_exact(3)
+            .map(|x| [x[0], x[1], x[2]])
+            .collect(),
+        PrimitiveType::TriangleStrip => indices
+            .windows(3)
+            .enumerate()
+            .map(|(i, x)| {
+                if i % 2 == 0 {
+                    [x[0], x[1], x[2]]
+                } else {
+                    [x[1], x[0], x[2]]
+                }
+            })
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Returns the lines made up by the given indices. Other types of primitive
+/// produce no lines.
+pub(crate) fn lines(indices: &[u32], primitives: PrimitiveType) -> Vec<[u32; 2]> {
+    match primitives {
+        PrimitiveType::LinesList => indices.chunks_exact(2).map(|x| [x[0], x[1]]).collect(),
+        _ => Vec::new(),
+    }
+}
+
+#[test]
+pub fn ensure_strip_triangles_match_quad_triangles() {
+    // The triangles of a `Quad` strip, as described by its CCW test.
+    let triangles = triangles(&[0, 1, 2, 3], PrimitiveType::TriangleStrip);
+    assert_eq!(triangles, vec![[0, 1, 2], [2, 1, 3]]);
+}
@@ -0,0 +1,262 @@
+//! A module for exporting shapes to the Wavefront OBJ file format.
+
+use super::{lines, resolve_indices, triangles};
+use crate::errors::ExportError;
+use crate::index::PrimitiveType;
+use crate::vertex::Vertex;
+
+use std::io::Write;
+
+/// Writes shapes to a stream in the Wavefront OBJ file format.
+///
+/// Multiple shapes can be written to the same stream, each as a separate object
+/// with an optional name. The positions, normals, and texture coordinates of every
+/// vertex are written, along with the faces (or lines) that reference them. As
+/// OBJ texture coordinates are also measured from the bottom-left of an image,
+/// they are written unmodified.
+pub struct ObjWriter<W: Write> {
+    writer: W,
+    num_vertices: usize,
+}
+
+impl<W: Write> ObjWriter<W> {
+    /// Create a new `ObjWriter` object that writes to the given stream.
+    pub fn new(writer: W) -> ObjWriter<W> {
+        ObjWriter {
+            writer,
+            num_vertices: 0,
+        }
+    }
+
+    /// Write a shape to the stream, given its vertices, its indices (if any), and
+    /// the type of primitive they make up (e.g. as returned by the `build_vertices`,
+    /// `build_indices` and `primitives` methods of a builder). When a name is given,
+    /// the shape is written as a named object, with any whitespace in the name
+    /// replaced by underscores.
+    ///
+    /// An error is returned (before anything is written) if the shape is not made
+    /// up of triangles or lines, as OBJ files have no equivalent of other types of
+    /// primitive (e.g. points, or line strips).
+    pub fn write_shape(
+        &mut self,
+        name: Option<&str>,
+        vertices: &[Vertex],
+        indices: Option<&[u32]>,
+        primitives: PrimitiveType,
+    ) -> Result<(), ExportError> {
+        match primitives {
+            PrimitiveType::TrianglesList
+            | PrimitiveType::TriangleStrip
+            | PrimitiveType::LinesList => {}
+            _ => return Err(ExportError::UnsupportedPrimitive(primitives)),
+        }
+
+        if let Some(name) = name {
+            let name = name
+                .chars()
+                .map(|x| if x.is_whitespace() { '_' } else { x })
+                .collect::<String>();
+            writeln!(self.writer, "o {}", name)?;
+        }
+
+        for vertex in vertices {
+            let [x, y, z] = vertex.position;
+            writeln!(self.writer, "v {} {} {}", x, y, z)?;
+        }
+        for vertex in vertices {
+            let [u, v] = vertex.texcoord;
+            writeln!(self.writer, "vt {} {}", u, v)?;
+        }
+        for vertex in vertices {
+            let [x, y, z] = vertex.normal;
+            writeln!(self.writer, "vn {} {} {}", x, y, z)?;
+        }
+
+        // OBJ indices start from one, and are shared by every shape in the file.
+        let indices = resolve_indices(vertices.len(), indices);
+        let offset = self.num_vertices + 1;
+        for triangle in triangles(&indices, primitives) {
+            write!(self.writer, "f")?;
+            for index in triangle.iter() {
+                let index = *index as usize + offset;
+                write!(self.writer, " {}/{}/{}", index, index, index)?;
+            }
+            writeln!(self.writer)?;
+        }
+        for line in lines(&indices, primitives) {
+            let (a, b) = (line[0] as usize + offset, line[1] as usize + offset);
+            writeln!(self.writer, "l {}/{} {}/{}", a, a, b, b)?;
+        }
+
+        self.num_vertices += vertices.len();
+        Ok(())
+    }
+
+    /// Consume the `ObjWriter` object, returning the underlying stream.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Write a single shape to the given stream in the Wavefront OBJ file format. See
+/// `ObjWriter::write_shape` for details.
+pub fn write_obj<W: Write>(
+    writer: W,
+    name: Option<&str>,
+    vertices: &[Vertex],
+    indices: Option<&[u32]>,
+    primitives: PrimitiveType,
+) -> Result<(), ExportError> {
+    ObjWriter::new(writer).write_shape(name, vertices, indices, primitives)
+}
+
+/// Parse the vertices of each face (or line) of an OBJ file written by `ObjWriter`,
+/// in the order in which they were written, along with the names of its objects.
+#[cfg(test)]
+fn parse_obj(text: &str) -> (Vec<Vertex>, Vec<String>) {
+    let (mut positions, mut texcoords, mut normals) = (Vec::new(), Vec::new(), Vec::new());
+    let (mut vertices, mut names) = (Vec::new(), Vec::new());
+    for line in text.lines() {
+        let mut tokens = line.split_whitespace();
+        let keyword = tokens.next().expect("Empty line");
+        let values = tokens.collect::<Vec<&str>>();
+        let floats = || {
+            values
+                .iter()
+                .map(|x| x.parse::<f32>().expect("Invalid float"))
+                .collect::<Vec<f32>>()
+        };
+        match keyword {
+            "o" => names.push(values[0].to_string()),
+            "v" => positions.push(floats()),
+            "vt" => texcoords.push(floats()),
+            "vn" => normals.push(floats()),
+            "f" | "l" => {
+                for corner in &values {
+                    let indices = corner
+                        .split('/')
+                        .map(|x| x.parse::<usize>().expect("Invalid index") - 1)
+                        .collect::<Vec<usize>>();
+                    let normal = if keyword == "f" {
+                        normals[indices[2]].clone()
+                    } else {
+                        normals[indices[0]].clone()
+                    };
+                    vertices.push(Vertex {
+                        position: [
+                            positions[indices[0]][0],
+                            positions[indices[0]][1],
+                            positions[indices[0]][2],
+                        ],
+                        normal: [normal[0], normal[1], normal[2]],
+                        texcoord: [texcoords[indices[1]][0], texcoords[indices[1]][1]],
+                    });
+                }
+            }
+            _ => panic!("Unexpected keyword {}", keyword),
+        }
+    }
+    (vertices, names)
+}
+
+#[cfg(test)]
+use crate::shape::ShapeBuilder;
+
+#[cfg(test)]
+fn ensure_round_trip<B: ShapeBuilder>(builder: B) {
+    let vertices = builder.build_vertices().expect("Failed to build vertices");
+    let indices = builder.build_indices().expect("Failed to build indices");
+    let mut buffer = Vec::<u8>::new();
+    write_obj(
+        &mut buffer,
+        Some("shape"),
+        &vertices,
+        Some(&indices),
+        builder.primitives(),
+    )
+    .expect("Failed to write OBJ");
+
+    let (parsed, names) = parse_obj(&String::from_utf8(buffer).expect("Invalid UTF-8"));
+    let expected = triangles(&indices, builder.primitives())
+        .iter()
+        .flat_map(|x| x.to_vec())
+        .chain(
+            lines(&indices, builder.primitives())
+                .iter()
+                .flat_map(|x| x.to_vec()),
+        )
+        .map(|x| vertices[x as usize])
+        .collect::<Vec<Vertex>>();
+    assert_eq!(names, vec!["shape".to_string()]);
+    assert_eq!(parsed.len(), expected.len());
+    for (a, b) in parsed.iter().zip(expected.iter()) {
+        assert_eq!(a.position, b.position);
+        assert_eq!(a.normal, b.normal);
+        assert_eq!(a.texcoord, b.texcoord);
+    }
+}
+
+#[test]
+pub fn ensure_obj_round_trips_vertices() {
+    ensure_round_trip(crate::axes::AxesBuilder::new());
+    ensure_round_trip(crate::cuboid::CuboidBuilder::new().scale(2.0, 3.0, 4.0));
+    ensure_round_trip(
+        crate::plane::PlaneBuilder::new()
+            .with_divisions(3, 2)
+            .indexed(),
+    );
+    ensure_round_trip(crate::quad::QuadBuilder::new());
+    ensure_round_trip(crate::sphere::SphereBuilder::new().indexed());
+    ensure_round_trip(crate::torus::TorusBuilder::new().rotate_x(0.3));
+}
+
+#[test]
+pub fn ensure_obj_indices_are_offset_per_shape() {
+    let vertices = crate::quad::QuadBuilder::new()
+        .build_vertices()
+        .expect("Failed to build vertices");
+    let mut writer = ObjWriter::new(Vec::<u8>::new());
+    writer
+        .write_shape(
+            Some("first quad"),
+            &vertices,
+            None,
+            PrimitiveType::TriangleStrip,
+        )
+        .expect("Failed to write OBJ");
+    writer
+        .write_shape(None, &vertices, None, PrimitiveType::TriangleStrip)
+        .expect("Failed to write OBJ");
+    let text = String::from_utf8(writer.into_inner()).expect("Invalid UTF-8");
+    let faces = text
+        .lines()
+        .filter(|x| x.starts_with("f "))
+        .collect::<Vec<&str>>();
+    assert_eq!(
+        faces,
+        vec![
+            "f 1/1/1 2/2/2 3/3/3",
+            "f 3/3/3 2/2/2 4/4/4",
+            "f 5/5/5 6/6/6 7/7/7",
+            "f 7/7/7 6/6/6 8/8/8"
+        ]
+    );
+
+    let (_, names) = parse_obj(&text);
+    assert_eq!(names, vec!["first_quad".to_string()]);
+}
+
+#[test]
+pub fn ensure_obj_rejects_unsupported_primitives() {
+    let vertices = crate::axes::AxesBuilder::new()
+        .build_vertices()
+        .expect("Failed to build vertices");
+    for primitives in &[PrimitiveType::Points, PrimitiveType::LineStrip] {
+        let mut obj = Vec::<u8>::new();
+        match write_obj(&mut obj, Some("axes"), &vertices, None, *primitives) {
+            Err(ExportError::UnsupportedPrimitive(x)) if x == *primitives => {}
+            _ => panic!("Expected an unsupported primitive error"),
+        }
+        assert!(obj.is_empty());
+    }
+}
@@ -0,0 +1,406 @@
+//! A module for exporting shapes to the PLY (Polygon File Format) file format.
+
+use super::{lines, resolve_indices, triangles};
+use crate::errors::ExportError;
+use crate::index::PrimitiveType;
+use crate::vertex::Vertex;
+
+use std::io::Write;
+
+/// The names of the float properties written for every vertex.
+const VERTEX_PROPERTIES: [&str; 8] = ["x", "y", "z", "nx", "ny", "nz", "s", "t"];
+
+/// The names of the uchar properties written for the colour of every vertex.
+const COLOR_PROPERTIES: [&str; 4] = ["red", "green", "blue", "alpha"];
+
+/// The encoding of a PLY file.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PlyFormat {
+    /// Each element is written as a line of text.
+    Ascii,
+
+    /// Each element is written as little-endian binary data.
+    BinaryLittleEndian,
+}
+
+/// Writes shapes to a stream in the PLY file format.
+///
+/// The position (`x`, `y`, `z`), normal (`nx`, `ny`, `nz`), and texture coordinate
+/// (`s`, `t`) of every vertex are written as float properties, followed by its
+/// colour (`red`, `green`, `blue`, `alpha`) as uchar properties when colours are
+/// given, and any custom float properties. Triangles are written as `face`
+/// elements, and lines (e.g. of `Axes`) as `edge` elements, unless the writer is in
+/// point-only mode, in which case only the vertices are written.
+pub struct PlyWriter<'a> {
+    format: PlyFormat,
+    colors: Option<&'a [[f32; 4]]>,
+    properties: Vec<(String, &'a [f32])>,
+    points: bool,
+}
+
+impl<'a> PlyWriter<'a> {
+    /// Create a new `PlyWriter` object that writes files with the given encoding.
+    pub fn new(format: PlyFormat) -> PlyWriter<'a> {
+        PlyWriter {
+            format,
+            colors: None,
+            properties: Vec::new(),
+            points: false,
+        }
+    }
+
+    /// Write the given RGBA colour for each vertex (e.g. the `color` attribute of
+    /// vertices built using `build_vertices_as::<ColorVertex>()`). Each component
+    /// is clamped to the range [0, 1], and written as a uchar in the range [0, 255].
+    pub fn with_colors(mut self, colors: &'a [[f32; 4]]) -> Self {
+        self.colors = Some(colors);
+        self
+    }
+
+    /// Write a custom float property for each vertex, with the given name (with any
+    /// whitespace replaced by underscores). Properties are written in the order in
+    /// which they are added, and the shape fails to export if the name of a
+    /// property is empty, or is used by another property (including the position, normal,
+    /// texture coordinate, and colour properties).
+    pub fn with_property(mut self, name: &str, values: &'a [f32]) -> Self {
+        let name = name
+            .chars()
+            .map(|x| if x.is_whitespace() { '_' } else { x })
+            .collect::<String>();
+        self.properties.push((name, values));
+        self
+    }
+
+    /// Write only the vertices of each shape, without any faces or edges (e.g. for
+    /// point-cloud tools).
+    pub fn points_only(mut self) -> Self {
+        self.points = true;
+        self
+    }
+
+    /// Write a shape to the given stream, given its vertices, its indices (if any),
+    /// and the type of primitive they make up (e.g. as returned by the
+    /// `build_vertices`, `build_indices` and `primitives` methods of a builder).
+    ///
+    /// An error is returned if the colours or any custom property do not hold
+    /// exactly one value for each vertex.
+    pub fn write<W: Write>(
+        &self,
+        mut writer: W,
+        vertices: &[Vertex],
+        indices: Option<&[u32]>,
+        primitives: PrimitiveType,
+    ) -> Result<(), ExportError> {
+        if self.colors.is_some_and(|x| x.len() != vertices.len()) {
+            return Err(ExportError::PropertyLengthMismatch("color".to_string()));
+        }
+        if let Some((name, _)) = self
+            .properties
+            .iter()
+            .find(|(_, values)| values.len() != vertices.len())
+        {
+            return Err(ExportError::PropertyLengthMismatch(name.clone()));
+        }
+        for (index, (name, _)) in self.properties.iter().enumerate() {
+            if name.is_empty() {
+                return Err(ExportError::EmptyPropertyName);
+            }
+            let mut reserved = VERTEX_PROPERTIES.iter().chain(COLOR_PROPERTIES.iter());
+            if reserved.any(|x| x == name)
+                || self.properties[..index].iter().any(|(x, _)| x == name)
+            {
+                return Err(ExportError::DuplicateProperty(name.clone()));
+            }
+        }
+
+        let (faces, edges) = if self.points {
+            (Vec::new(), Vec::new())
+        } else {
+            let indices = resolve_indices(vertices.len(), indices);
+            (triangles(&indices, primitives), lines(&indices, primitives))
+        };
+
+        self.write_header(&mut writer, vertices.len(), faces.len(), edges.len())?;
+        for (index, vertex) in vertices.iter().enumerate() {
+            let floats = vertex
+                .position
+                .iter()
+                .chain(vertex.normal.iter())
+                .chain(vertex.texcoord.iter())
+                .cloned()
+                .collect::<Vec<f32>>();
+            let colors = self.colors.map(|x| x[index]).map(|x| {
+                let mut color = [0u8; 4];
+                for (channel, value) in color.iter_mut().zip(x.iter()) {
+                    *channel = (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+                }
+                color
+            });
+            let properties = self
+                .properties
+                .iter()
+                .map(|(_, values)| values[index])
+                .collect::<Vec<f32>>();
+
+            match self.format {
+                PlyFormat::Ascii => {
+                    let mut values = floats.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+                    values.extend(colors.iter().flat_map(|x| x.iter()).map(|x| x.to_string()));
+                    values.extend(properties.iter().map(|x| x.to_string()));
+                    writeln!(writer, "{}", values.join(" "))?;
+                }
+                PlyFormat::BinaryLittleEndian => {
+                    for value in &floats {
+                        writer.write_all(&value.to_le_bytes())?;
+                    }
+                    if let Some(color) = colors {
+                        writer.write_all(&color)?;
+                    }
+                    for value in &properties {
+                        writer.write_all(&value.to_le_bytes())?;
+                    }
+                }
+            }
+        }
+
+        for face in &faces {
+            match self.format {
+                PlyFormat::Ascii => writeln!(writer, "3 {} {} {}", face[0], face[1], face[2])?,
+                PlyFormat::BinaryLittleEndian => {
+                    writer.write_all(&[3u8])?;
+                    for index in face {
+                        writer.write_all(&index.to_le_bytes())?;
+                    }
+                }
+            }
+        }
+
+        for edge in &edges {
+            match self.format {
+                PlyFormat::Ascii => writeln!(writer, "{} {}", edge[0], edge[1])?,
+                PlyFormat::BinaryLittleEndian => {
+                    for index in edge {
+                        writer.write_all(&index.to_le_bytes())?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Write the header of a file holding the given number of each element.
+    fn write_header<W: Write>(
+        &self,
+        writer: &mut W,
+        num_vertices: usize,
+        num_faces: usize,
+        num_edges: usize,
+    ) -> Result<(), ExportError> {
+        writeln!(writer, "ply")?;
+        match self.format {
+            PlyFormat::Ascii => writeln!(writer, "format ascii 1.0")?,
+            PlyFormat::BinaryLittleEndian => writeln!(writer, "format binary_little_endian 1.0")?,
+        }
+        writeln!(writer, "comment generated by glium_shapes")?;
+
+        writeln!(writer, "element vertex {}", num_vertices)?;
+        for name in &VERTEX_PROPERTIES {
+            writeln!(writer, "property float {}", name)?;
+        }
+        if self.colors.is_some() {
+            for name in &COLOR_PROPERTIES {
+                writeln!(writer, "property uchar {}", name)?;
+            }
+        }
+        for (name, _) in &self.properties {
+            writeln!(writer, "property float {}", name)?;
+        }
+
+        if num_faces > 0 {
+            writeln!(writer, "element face {}", num_faces)?;
+            writeln!(writer, "property list uchar uint vertex_indices")?;
+        }
+        if num_edges > 0 {
+            writeln!(writer, "element edge {}", num_edges)?;
+            writeln!(writer, "property uint vertex1")?;
+            writeln!(writer, "property uint vertex2")?;
+        }
+        writeln!(writer, "end_header")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+use crate::shape::ShapeBuilder;
+
+/// Split a PLY file written by `PlyWriter` into its header lines and its body.
+#[cfg(test)]
+fn split_header(bytes: &[u8]) -> (Vec<String>, &[u8]) {
+    let end = b"end_header\n";
+    let position = bytes
+        .windows(end.len())
+        .position(|x| x == end)
+        .expect("Missing end_header");
+    let header = String::from_utf8(bytes[..position].to_vec()).expect("Invalid UTF-8");
+    (
+        header.lines().map(|x| x.to_string()).collect(),
+        &bytes[position + end.len()..],
+    )
+}
+
+#[test]
+pub fn ensure_ascii_ply_round_trips_vertices() {
+    let builder = crate::cuboid::CuboidBuilder::new().indexed();
+    let vertices = builder.build_vertices().expect("Failed to build vertices");
+    let indices = builder.build_indices().expect("Failed to build indices");
+    let mut buffer = Vec::<u8>::new();
+    PlyWriter::new(PlyFormat::Ascii)
+        .write(&mut buffer, &vertices, Some(&indices), builder.primitives())
+        .expect("Failed to write PLY");
+
+    let (header, body) = split_header(&buffer);
+    assert_eq!(header[1], "format ascii 1.0");
+    assert!(header.contains(&format!("element vertex {}", vertices.len())));
+    assert!(header.contains(&format!("element face {}", indices.len() / 3)));
+    let lines = std::str::from_utf8(body)
+        .expect("Invalid UTF-8")
+        .lines()
+        .collect::<Vec<&str>>();
+    assert_eq!(lines.len(), vertices.len() + indices.len() / 3);
+    for (line, vertex) in lines.iter().zip(vertices.iter()) {
+        let values = line
+            .split(' ')
+            .map(|x| x.parse::<f32>().expect("Invalid float"))
+            .collect::<Vec<f32>>();
+        assert_eq!(&values[0..3], &vertex.position[..]);
+        assert_eq!(&values[3..6], &vertex.normal[..]);
+        assert_eq!(&values[6..8], &vertex.texcoord[..]);
+    }
+    for (line, face) in lines[vertices.len()..].iter().zip(indices.chunks(3)) {
+        assert_eq!(*line, format!("3 {} {} {}", face[0], face[1], face[2]));
+    }
+}
+
+#[test]
+pub fn ensure_binary_ply_holds_colors_and_properties() {
+    let builder = crate::quad::QuadBuilder::new();
+    let vertices = builder.build_vertices().expect("Failed to build vertices");
+    let colors = vec![[1.0, 0.5, 0.0, 1.0]; vertices.len()];
+    let weights = vec![0.25; vertices.len()];
+    let mut buffer = Vec::<u8>::new();
+    PlyWriter::new(PlyFormat::BinaryLittleEndian)
+        .with_colors(&colors)
+        .with_property("bone weight", &weights)
+        .write(&mut buffer, &vertices, None, builder.primitives())
+        .expect("Failed to write PLY");
+
+    let (header, body) = split_header(&buffer);
+    assert_eq!(header[1], "format binary_little_endian 1.0");
+    assert!(header.contains(&"property uchar alpha".to_string()));
+    assert_eq!(header[header.len() - 2], "element face 2");
+
+    // Each vertex holds eight floats, four colour channels, and one weight. Each
+    // face holds a count, followed by three indices.
+    let stride = 8 * 4 + 4 + 4;
+    assert_eq!(body.len(), vertices.len() * stride + 2 * (1 + 3 * 4));
+    for (index, vertex) in vertices.iter().enumerate() {
+        let bytes = &body[index * stride..(index + 1) * stride];
+        let float =
+            |x: usize| f32::from_le_bytes([bytes[x], bytes[x + 1], bytes[x + 2], bytes[x + 3]]);
+        assert_eq!(float(0), vertex.position[0]);
+        assert_eq!(float(28), vertex.texcoord[1]);
+        assert_eq!(&bytes[32..36], &[255, 128, 0, 255]);
+        assert_eq!(float(36), 0.25);
+    }
+}
+
+#[test]
+pub fn ensure_ply_writes_lines_as_edges() {
+    let builder = crate::axes::AxesBuilder::new();
+    let vertices = builder.build_vertices().expect("Failed to build vertices");
+    let mut buffer = Vec::<u8>::new();
+    PlyWriter::new(PlyFormat::Ascii)
+        .write(&mut buffer, &vertices, None, builder.primitives())
+        .expect("Failed to write PLY");
+    let (header, body) = split_header(&buffer);
+    assert!(header.contains(&"element edge 3".to_string()));
+    assert!(!header.iter().any(|x| x.starts_with("element face")));
+    let lines = std::str::from_utf8(body)
+        .expect("Invalid UTF-8")
+        .lines()
+        .collect::<Vec<_>>();
+    assert_eq!(&lines[vertices.len()..], &["0 1", "2 3", "4 5"]);
+}
+
+#[test]
+pub fn ensure_point_only_ply_has_no_faces() {
+    let builder = crate::sphere::SphereBuilder::new().indexed();
+    let vertices = builder.build_vertices().expect("Failed to build vertices");
+    let indices = builder.build_indices().expect("Failed to build indices");
+    let mut buffer = Vec::<u8>::new();
+    PlyWriter::new(PlyFormat::Ascii)
+        .points_only()
+        .write(&mut buffer, &vertices, Some(&indices), builder.primitives())
+        .expect("Failed to write PLY");
+    let (header, body) = split_header(&buffer);
+    assert_eq!(
+        header.iter().filter(|x| x.starts_with("element")).count(),
+        1
+    );
+    assert_eq!(
+        std::str::from_utf8(body)
+            .expect("Invalid UTF-8")
+            .lines()
+            .count(),
+        vertices.len()
+    );
+}
+
+#[test]
+pub fn ensure_ply_rejects_mismatched_properties() {
+    let vertices = crate::quad::QuadBuilder::new()
+        .build_vertices()
+        .expect("Failed to build vertices");
+    let result = PlyWriter::new(PlyFormat::Ascii)
+        .with_property("weight", &[1.0])
+        .write(Vec::new(), &vertices, None, PrimitiveType::TriangleStrip);
+    match result {
+        Err(ExportError::PropertyLengthMismatch(ref name)) if name == "weight" => {}
+        _ => panic!("Expected a property length mismatch error"),
+    }
+}
+
+#[test]
+pub fn ensure_ply_rejects_duplicate_properties() {
+    let vertices = crate::quad::QuadBuilder::new()
+        .build_vertices()
+        .expect("Failed to build vertices");
+    let values = [0.0; 4];
+    for names in &[&["x"][..], &["nx"], &["s"], &["red"], &["weight", "weight"]] {
+        let writer = names
+            .iter()
+            .fold(PlyWriter::new(PlyFormat::Ascii), |writer, name| {
+                writer.with_property(name, &values)
+            });
+        match writer.write(Vec::new(), &vertices, None, PrimitiveType::TriangleStrip) {
+            Err(ExportError::DuplicateProperty(ref name)) if name == names[0] => {}
+            _ => panic!("Expected a duplicate property error"),
+        }
+    }
+}
+
+#[test]
+pub fn ensure_ply_rejects_empty_property_names() {
+    let vertices = crate::quad::QuadBuilder::new()
+        .build_vertices()
+        .expect("Failed to build vertices");
+    let values = [0.0; 4];
+    let result = PlyWriter::new(PlyFormat::Ascii)
+        .with_property("weight", &values)
+        .with_property("", &values)
+        .write(Vec::new(), &vertices, None, PrimitiveType::TriangleStrip);
+    match result {
+        Err(ExportError::EmptyPropertyName) => {}
+        _ => panic!("Expected an empty property name error"),
+    }
+}
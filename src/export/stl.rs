@@ -0,0 +1,268 @@
+//! A module for exporting shapes to the STL file format (e.g. for 3D printing).
+
+extern crate cgmath;
+
+use self::cgmath::*;
+use super::{resolve_indices, triangles};
+use crate::errors::ExportError;
+use crate::index::PrimitiveType;
+use crate::vertex::Vertex;
+
+use std::io::Write;
+
+/// A triangle of a shape, as written to an STL file.
+struct Facet {
+    normal: [f32; 3],
+    corners: [[f32; 3]; 3],
+}
+
+/// Returns the corners and facet normal of each triangle of a shape, or an error
+/// if the shape is not made up of triangles.
+///
+/// The facet normal is the normalised mean of the normals of its corners, such
+/// that faceted shapes (e.g. `Cuboid`) keep the normals computed by their builder.
+/// Should the mean be zero, the normal is computed from the corners instead.
+fn facets(
+    vertices: &[Vertex],
+    indices: Option<&[u32]>,
+    primitives: PrimitiveType,
+) -> Result<Vec<Facet>, ExportError> {
+    match primitives {
+        PrimitiveType::TrianglesList | PrimitiveType::TriangleStrip => {}
+        _ => return Err(ExportError::UnsupportedPrimitive(primitives)),
+    }
+
+    let indices = resolve_indices(vertices.len(), indices);
+    Ok(triangles(&indices, primitives)
+        .iter()
+        .map(|triangle| {
+            let corners = [
+                vertices[triangle[0] as usize],
+                vertices[triangle[1] as usize],
+                vertices[triangle[2] as usize],
+            ];
+            let normal = corners
+                .iter()
+                .map(|x| Vector3::from(x.normal))
+                .sum::<Vector3<f32>>();
+            let normal = if normal.magnitude2() > 0.0 {
+                normal.normalize()
+            } else {
+                let a = Vector3::from(corners[0].position);
+                let b = Vector3::from(corners[1].position);
+                let c = Vector3::from(corners[2].position);
+                (b - a).cross(c - a).normalize()
+            };
+            Facet {
+                normal: normal.into(),
+                corners: [
+                    corners[0].position,
+                    corners[1].position,
+                    corners[2].position,
+                ],
+            }
+        })
+        .collect())
+}
+
+/// Write a shape to the given stream in the binary STL file format, given its
+/// vertices, its indices (if any), and the type of primitive they make up (e.g. as
+/// returned by the `build_vertices`, `build_indices` and `primitives` methods of a
+/// builder). The name (if any) is written to the 80 byte header, truncated if
+/// necessary. As readers detect ASCII STL files by a header starting with
+/// `solid`, such names are prefixed with `glium_shapes` to keep the file binary.
+///
+/// An error is returned if the shape is not made up of triangles, as STL files
+/// can only hold triangles.
+pub fn write_stl_binary<W: Write>(
+    mut writer: W,
+    name: Option<&str>,
+    vertices: &[Vertex],
+    indices: Option<&[u32]>,
+    primitives: PrimitiveType,
+) -> Result<(), ExportError> {
+    let facets = facets(vertices, indices, primitives)?;
+
+    let mut header = [0u8; 80];
+    let name = match name {
+        Some(name) if name.trim_start().to_ascii_lowercase().starts_with("solid") => {
+            format!("glium_shapes {}", name)
+        }
+        Some(name) => name.to_string(),
+        None => "glium_shapes".to_string(),
+    };
+    let name = name.as_bytes();
+    let length = name.len().min(header.len());
+    header[..length].copy_from_slice(&name[..length]);
+    writer.write_all(&header)?;
+    writer.write_all(&(facets.len() as u32).to_le_bytes())?;
+
+    for facet in &facets {
+        let corners = facet.corners.iter().flat_map(|x| x.iter());
+        for value in facet.normal.iter().chain(corners) {
+            writer.write_all(&value.to_le_bytes())?;
+        }
+        // The attribute byte count, which is unused.
+        writer.write_all(&0u16.to_le_bytes())?;
+    }
+    Ok(())
+}
+
+/// Write a shape to the given stream in the ASCII STL file format, in the same
+/// manner as `write_stl_binary`. The name (if any) is written as the name of the
+/// solid, with any whitespace replaced by underscores.
+pub fn write_stl_ascii<W: Write>(
+    mut writer: W,
+    name: Option<&str>,
+    vertices: &[Vertex],
+    indices: Option<&[u32]>,
+    primitives: PrimitiveType,
+) -> Result<(), ExportError> {
+    let facets = facets(vertices, indices, primitives)?;
+
+    let name = name
+        .unwrap_or("glium_shapes")
+        .chars()
+        .map(|x| if x.is_whitespace() { '_' } else { x })
+        .collect::<String>();
+    writeln!(writer, "solid {}", name)?;
+    for facet in &facets {
+        let normal = facet.normal;
+        writeln!(
+            writer,
+            "  facet normal {:e} {:e} {:e}",
+            normal[0], normal[1], normal[2]
+        )?;
+        writeln!(writer, "    outer loop")?;
+        for corner in &facet.corners {
+            writeln!(
+                writer,
+                "      vertex {:e} {:e} {:e}",
+                corner[0], corner[1], corner[2]
+            )?;
+        }
+        writeln!(writer, "    endloop")?;
+        writeln!(writer, "  endfacet")?;
+    }
+    writeln!(writer, "endsolid {}", name)?;
+    Ok(())
+}
+
+#[cfg(test)]
+use crate::shape::ShapeBuilder;
+
+#[cfg(test)]
+fn read_f32(bytes: &[u8], offset: usize) -> f32 {
+    let mut value = [0u8; 4];
+    value.copy_from_slice(&bytes[offset..offset + 4]);
+    f32::from_le_bytes(value)
+}
+
+#[test]
+pub fn ensure_binary_stl_matches_cuboid_facets() {
+    let builder = crate::cuboid::CuboidBuilder::new()
+        .scale(2.0, 3.0, 4.0)
+        .indexed();
+    let vertices = builder.build_vertices().expect("Failed to build vertices");
+    let indices = builder.build_indices().expect("Failed to build indices");
+    let mut buffer = Vec::<u8>::new();
+    write_stl_binary(
+        &mut buffer,
+        Some("cuboid"),
+        &vertices,
+        Some(&indices),
+        builder.primitives(),
+    )
+    .expect("Failed to write STL");
+
+    // Each facet holds twelve floats, followed by a two byte attribute.
+    let count = u32::from_le_bytes([buffer[80], buffer[81], buffer[82], buffer[83]]);
+    assert_eq!(&buffer[..6], b"cuboid");
+    assert_eq!(count, 6 * 2);
+    assert_eq!(buffer.len(), 84 + 50 * count as usize);
+
+    for (facet, triangle) in indices.chunks(3).enumerate() {
+        let offset = 84 + 50 * facet;
+        let corner = vertices[triangle[0] as usize];
+        for axis in 0..3 {
+            assert_eq!(read_f32(&buffer, offset + 4 * axis), corner.normal[axis]);
+        }
+        for (index, vertex) in triangle.iter().enumerate() {
+            for axis in 0..3 {
+                let value = read_f32(&buffer, offset + 12 + 12 * index + 4 * axis);
+                assert_eq!(value, vertices[*vertex as usize].position[axis]);
+            }
+        }
+    }
+}
+
+#[test]
+pub fn ensure_ascii_stl_facets_face_outward() {
+    let builder = crate::sphere::SphereBuilder::new();
+    let vertices = builder.build_vertices().expect("Failed to build vertices");
+    let mut buffer = Vec::<u8>::new();
+    write_stl_ascii(&mut buffer, None, &vertices, None, builder.primitives())
+        .expect("Failed to write STL");
+    let text = String::from_utf8(buffer).expect("Invalid UTF-8");
+
+    let floats = |line: &str| {
+        line.split_whitespace()
+            .filter_map(|x| x.parse::<f32>().ok())
+            .collect::<Vec<f32>>()
+    };
+    let lines = text.lines().collect::<Vec<&str>>();
+    assert_eq!(lines[0], "solid glium_shapes");
+    assert_eq!(lines[lines.len() - 1], "endsolid glium_shapes");
+    let facets = lines[1..lines.len() - 1].chunks(7).collect::<Vec<_>>();
+    assert_eq!(facets.len(), vertices.len() / 3);
+    for facet in facets {
+        let normal = Vector3::from([
+            floats(facet[0])[0],
+            floats(facet[0])[1],
+            floats(facet[0])[2],
+        ]);
+        let centre = (2..5)
+            .map(|x| floats(facet[x]))
+            .map(|x| Vector3::new(x[0], x[1], x[2]))
+            .sum::<Vector3<f32>>()
+            / 3.0;
+        assert_relative_eq!(normal.magnitude(), 1.0, epsilon = 1e-5);
+        assert!(normal.dot(centre) > 0.0);
+    }
+}
+
+#[test]
+pub fn ensure_stl_rejects_lines() {
+    let builder = crate::axes::AxesBuilder::new();
+    let vertices = builder.build_vertices().expect("Failed to build vertices");
+    let result = write_stl_binary(Vec::new(), None, &vertices, None, builder.primitives());
+    match result {
+        Err(ExportError::UnsupportedPrimitive(PrimitiveType::LinesList)) => {}
+        _ => panic!("Expected an unsupported primitive error"),
+    }
+    let result = write_stl_ascii(Vec::new(), None, &vertices, None, builder.primitives());
+    assert!(result.is_err());
+}
+
+#[test]
+pub fn ensure_binary_stl_header_does_not_start_with_solid() {
+    let builder = crate::cuboid::CuboidBuilder::new();
+    let vertices = builder.build_vertices().expect("Failed to build vertices");
+    for name in &["solid", "Solid cube", "  solid"] {
+        let mut stl = Vec::<u8>::new();
+        write_stl_binary(&mut stl, Some(name), &vertices, None, builder.primitives())
+            .expect("Failed to write STL");
+        assert!(stl[..80].starts_with(format!("glium_shapes {}", name).as_bytes()));
+        assert_eq!(stl.len(), 84 + 12 * 50);
+    }
+    let mut stl = Vec::<u8>::new();
+    write_stl_binary(
+        &mut stl,
+        Some("cube"),
+        &vertices,
+        None,
+        builder.primitives(),
+    )
+    .expect("Failed to write STL");
+    assert!(stl[..80].starts_with(b"cube\0"));
+}
@@ -0,0 +1,340 @@
+//! A module for constructing icosphere shapes.
+
+extern crate cgmath;
+#[cfg(feature = "glium")]
+extern crate glium;
+
+use self::cgmath::*;
+use crate::errors::ShapeCreationError;
+use crate::index;
+#[cfg(feature = "glium")]
+use crate::mesh::Mesh;
+use crate::platonic::{ICOSAHEDRON_FACES, ICOSAHEDRON_VERTICES};
+use crate::shading::Shading;
+use crate::shape::{ShapeBuilder, ShapeOptions};
+use crate::vertex::Vertex;
+
+use std::convert::TryFrom;
+use std::f32;
+
+/// A polygonal `Icosphere` object.
+///
+/// This object is constructed using an `IcosphereBuilder` object.
+#[cfg(feature = "glium")]
+pub type Icosphere = Mesh;
+
+/// The maximum number of levels of subdivision supported by an `IcosphereBuilder`.
+/// Each level quadruples the number of vertices, such that this level already
+/// emits over a billion vertices (around 32 GB), and the next would emit
+/// almost as many as a `u32` index can address.
+pub const MAX_SUBDIVISIONS: usize = 12;
+
+/// Responsible for building and returning an `Icosphere` object.
+///
+/// An icosphere is built by recursively subdividing the faces of an icosahedron
+/// and projecting the resultant vertices onto a sphere, which distributes the
+/// triangles much more evenly than the latitude/longitude tessellation of a
+/// `SphereBuilder`. By default, the icosphere is defined as a unit-sphere
+/// (e.g. a radius of 1) with its centre-of-mass located at the origin. This
+/// can be overriden using the transformation methods on this object.
+///
+/// The resultant geometry is constructed to suit OpenGL defaults - assuming
+/// a right-handed coordinate system, front-facing polygons are defined in
+/// counter-clock-wise order. By default, vertex normals point in the direction
+/// of their respective face (such that the shape appears faceted when lit),
+/// although smooth normals can be requested instead. Vertex texture coordinates
+/// define the same spherical-projection as a `SphereBuilder`. Triangles that
+/// straddle the seam of the projection are given u coordinates greater than 1,
+/// such that they interpolate correctly when sampled with a repeating wrap mode.
+pub struct IcosphereBuilder {
+    options: ShapeOptions,
+    subdivisions: usize,
+}
+
+impl Default for IcosphereBuilder {
+    fn default() -> Self {
+        IcosphereBuilder {
+            options: ShapeOptions::default(),
+            subdivisions: 2,
+        }
+    }
+}
+
+impl IcosphereBuilder {
+    /// Create a new `IcosphereBuilder` object.
+    pub fn new() -> IcosphereBuilder {
+        Default::default()
+    }
+
+    /// Specify the number of times the faces of the icosahedron are subdivided.
+    /// Each level of subdivision splits every triangle into four, such that a
+    /// level of 0 produces an icosahedron. By default, the builder will use 2
+    /// levels of subdivision, and the shape fails to build if the level exceeds
+    /// `MAX_SUBDIVISIONS`.
+    pub fn with_subdivisions(mut self, level: usize) -> Self {
+        self.subdivisions = level;
+        self
+    }
+
+    /// Specify how vertex normals are generated. By default, the builder will
+    /// use `Shading::Faceted`.
+    pub fn with_shading(mut self, shading: Shading) -> Self {
+        self.options.shading = shading;
+        self
+    }
+
+    /// Returns the number of triangle faces in the resultant icosphere geometry
+    /// (saturating at `usize::MAX` when there are too many levels of subdivision).
+    pub fn num_faces(&self) -> usize {
+        u32::try_from(self.subdivisions)
+            .ok()
+            .and_then(|level| 4usize.checked_pow(level))
+            .and_then(|faces| faces.checked_mul(ICOSAHEDRON_FACES.len()))
+            .unwrap_or(usize::MAX)
+    }
+}
+
+impl ShapeBuilder for IcosphereBuilder {
+    fn options(&self) -> &ShapeOptions {
+        &self.options
+    }
+
+    fn options_mut(&mut self) -> &mut ShapeOptions {
+        &mut self.options
+    }
+
+    fn primitives(&self) -> index::PrimitiveType {
+        index::PrimitiveType::TrianglesList
+    }
+
+    fn build_unindexed_vertices(&self) -> Result<Vec<Vertex>, ShapeCreationError> {
+        // Ensure the number of vertices remains within bounds.
+        if self.subdivisions > MAX_SUBDIVISIONS {
+            return Err(ShapeCreationError::TooManySubdivisions);
+        }
+
+        // Build the icosahedron, then subdivide its faces.
+        let mut faces = ICOSAHEDRON_FACES
+            .iter()
+            .map(|face| {
+                [
+                    Vector3::<f32>::from(ICOSAHEDRON_VERTICES[face[0]]).normalize(),
+                    Vector3::<f32>::from(ICOSAHEDRON_VERTICES[face[1]]).normalize(),
+                    Vector3::<f32>::from(ICOSAHEDRON_VERTICES[face[2]]).normalize(),
+                ]
+            })
+            .collect::<Vec<[Vector3<f32>; 3]>>();
+
+        for _ in 0..self.subdivisions {
+            let mut subdivided = Vec::<[Vector3<f32>; 3]>::with_capacity(faces.len() * 4);
+            for [a, b, c] in faces {
+                let ab = (a + b).normalize();
+                let bc = (b + c).normalize();
+                let ca = (c + a).normalize();
+                subdivided.push([a, ab, ca]);
+                subdivided.push([b, bc, ab]);
+                subdivided.push([c, ca, bc]);
+                subdivided.push([ab, bc, ca]);
+            }
+            faces = subdivided;
+        }
+
+        // Compute the normal transformation matrix.
+        let normal_matrix = self.options.normal_matrix();
+
+        // Build vertex array.
+        let total_num_verts = self.num_unindexed_vertices();
+        let mut vertices = Vec::<Vertex>::with_capacity(total_num_verts);
+
+        for face in &faces {
+            // Compute face normal
+            let face_normal = (face[1] - face[0]).cross(face[2] - face[0]).normalize();
+
+            // Emit vertices.
+            let texcoords = spherical_texcoords(face);
+            for (vpos, texcoord) in face.iter().zip(texcoords.iter()) {
+                let pos = self.options.matrix * vpos.extend(1.0);
+                let normal = match self.options.shading {
+                    Shading::Faceted => face_normal,
+                    Shading::Smooth => *vpos,
+                };
+                vertices.push(Vertex {
+                    position: Point3::<f32>::from_homogeneous(pos).into(),
+                    normal: (normal_matrix * normal).normalize().into(),
+                    texcoord: *texcoord,
+                });
+            }
+        }
+
+        assert!(vertices.len() == total_num_verts);
+        Ok(vertices)
+    }
+
+    fn num_unindexed_vertices(&self) -> usize {
+        self.num_faces().saturating_mul(3)
+    }
+}
+
+/// Compute the spherical-projection texture coordinates of a triangle on the
+/// unit-sphere, fixing up the u coordinates of triangles that straddle the
+/// seam and of vertices located at the poles.
+fn spherical_texcoords(face: &[Vector3<f32>; 3]) -> [[f32; 2]; 3] {
+    let mut texcoords = [[0.0; 2]; 3];
+    for (pos, texcoord) in face.iter().zip(texcoords.iter_mut()) {
+        let u = pos.z.atan2(pos.x) / (2.0 * f32::consts::PI);
+        let v = pos.y.clamp(-1.0, 1.0).acos() / f32::consts::PI;
+        *texcoord = [if u < 0.0 { u + 1.0 } else { u }, v];
+    }
+
+    let min_u = texcoords.iter().map(|x| x[0]).fold(f32::MAX, f32::min);
+    let max_u = texcoords.iter().map(|x| x[0]).fold(f32::MIN, f32::max);
+    if max_u - min_u > 0.5 {
+        for texcoord in texcoords.iter_mut().filter(|x| x[0] < 0.5) {
+            texcoord[0] += 1.0;
+        }
+    }
+
+    for index in 0..3 {
+        if abs_diff_eq!(face[index].y.abs(), 1.0, epsilon = 1e-6) {
+            let others = [texcoords[(index + 1) % 3][0], texcoords[(index + 2) % 3][0]];
+            texcoords[index][0] = (others[0] + others[1]) * 0.5;
+        }
+    }
+
+    texcoords
+}
+
+#[test]
+pub fn ensure_default_icosphere_is_unit_sphere() {
+    let vertices = IcosphereBuilder::new()
+        .build_vertices()
+        .expect("Failed to build vertices");
+    for vertex in &vertices {
+        assert_ulps_eq!(Vector3::<f32>::from(vertex.position).magnitude(), 1.0);
+    }
+}
+
+#[test]
+pub fn ensure_default_icosphere_has_centroid_at_origin() {
+    let vertices = IcosphereBuilder::new()
+        .build_vertices()
+        .expect("Failed to build vertices");
+    let mut sum = Vector3::<f32>::zero();
+    for vertex in &vertices {
+        sum += Vector3::<f32>::from(vertex.position);
+    }
+    assert_ulps_eq!(sum, Vector3::<f32>::zero(), epsilon = 0.001);
+}
+
+#[test]
+pub fn ensure_default_icosphere_has_outward_facing_normals() {
+    let vertices = IcosphereBuilder::new()
+        .scale(2.0, 2.0, 2.0)
+        .build_vertices()
+        .expect("Failed to build vertices");
+    for vertex in &vertices {
+        let position = Vector3::<f32>::from(vertex.position);
+        let normal = Vector3::<f32>::from(vertex.normal);
+        assert!(position.dot(normal) > 0.0);
+    }
+}
+
+#[test]
+pub fn ensure_default_icosphere_has_ccw_triangles() {
+    let vertices = IcosphereBuilder::new()
+        .build_vertices()
+        .expect("Failed to build vertices");
+    for chunk in vertices.chunks(3) {
+        let v0 = Vector3::<f32>::from(chunk[0].position);
+        let v1 = Vector3::<f32>::from(chunk[1].position);
+        let v2 = Vector3::<f32>::from(chunk[2].position);
+        let eyepos = v0 + Vector3::<f32>::from(chunk[0].normal);
+        let e0 = v1 - v0;
+        let e1 = v2 - v0;
+        let n = e0.cross(e1);
+        assert!(n.dot(v0 - eyepos) <= 0.0);
+        assert!(n.dot(v1 - eyepos) <= 0.0);
+        assert!(n.dot(v2 - eyepos) <= 0.0);
+    }
+}
+
+#[test]
+pub fn ensure_default_icosphere_has_faceted_normals() {
+    let vertices = IcosphereBuilder::new()
+        .build_vertices()
+        .expect("Failed to build vertices");
+
+    for chunk in vertices.chunks(3) {
+        let v0 = Vector3::<f32>::from(chunk[0].position);
+        let v1 = Vector3::<f32>::from(chunk[1].position);
+        let v2 = Vector3::<f32>::from(chunk[2].position);
+        let n0 = Vector3::<f32>::from(chunk[0].normal);
+        let n1 = Vector3::<f32>::from(chunk[1].normal);
+        let n2 = Vector3::<f32>::from(chunk[2].normal);
+        let e0 = v1 - v0;
+        let e1 = v2 - v0;
+        let n = e0.cross(e1).normalize();
+        assert_ulps_eq!(n, n0, epsilon = 0.0001);
+        assert_ulps_eq!(n, n1, epsilon = 0.0001);
+        assert_ulps_eq!(n, n2, epsilon = 0.0001);
+    }
+}
+
+#[test]
+pub fn ensure_smooth_icosphere_has_radial_normals() {
+    let vertices = IcosphereBuilder::new()
+        .with_shading(Shading::Smooth)
+        .build_vertices()
+        .expect("Failed to build vertices");
+    for vertex in &vertices {
+        let position = Vector3::<f32>::from(vertex.position);
+        let normal = Vector3::<f32>::from(vertex.normal);
+        assert_ulps_eq!(position, normal, epsilon = 0.0001);
+    }
+}
+
+#[test]
+pub fn ensure_icosphere_has_evenly_distributed_triangles() {
+    let vertices = IcosphereBuilder::new()
+        .with_subdivisions(3)
+        .build_vertices()
+        .expect("Failed to build vertices");
+    let areas = vertices
+        .chunks(3)
+        .map(|chunk| {
+            let v0 = Vector3::<f32>::from(chunk[0].position);
+            let v1 = Vector3::<f32>::from(chunk[1].position);
+            let v2 = Vector3::<f32>::from(chunk[2].position);
+            (v1 - v0).cross(v2 - v0).magnitude()
+        })
+        .collect::<Vec<f32>>();
+    let min = areas.iter().cloned().fold(f32::MAX, f32::min);
+    let max = areas.iter().cloned().fold(f32::MIN, f32::max);
+    assert!(max / min < 1.5);
+}
+
+#[test]
+pub fn ensure_icosphere_has_expected_number_of_faces() {
+    for level in 0..4 {
+        let builder = IcosphereBuilder::new().with_subdivisions(level);
+        let vertices = builder.build_vertices().expect("Failed to build vertices");
+        assert_eq!(vertices.len(), 20 * 4usize.pow(level as u32) * 3);
+    }
+}
+
+#[test]
+pub fn ensure_icosphere_with_too_many_subdivisions_fails() {
+    for level in &[MAX_SUBDIVISIONS + 1, 40, usize::MAX] {
+        let builder = IcosphereBuilder::new().with_subdivisions(*level);
+        match builder.build_vertices() {
+            Err(ShapeCreationError::TooManySubdivisions) => {}
+            _ => panic!("Expected TooManySubdivisions"),
+        }
+    }
+    assert_eq!(
+        IcosphereBuilder::new().with_subdivisions(40).num_faces(),
+        usize::MAX
+    );
+    let builder = IcosphereBuilder::new().with_subdivisions(MAX_SUBDIVISIONS);
+    assert!(builder.num_unindexed_vertices() <= u32::MAX as usize);
+}
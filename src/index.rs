@@ -0,0 +1,370 @@
+//! A module for converting shape vertices into indexed geometry.
+
+extern crate cgmath;
+#[cfg(feature = "glium")]
+extern crate glium;
+
+use self::cgmath::*;
+use crate::errors::ShapeCreationError;
+use crate::shape::ShapeOptions;
+use crate::tangent;
+#[cfg(feature = "glium")]
+use crate::vertex::{ColorTangentVertex, ColorVertex, CubemapVertex, TangentVertex};
+use crate::vertex::{FromShapeVertex, ShapeVertex, Vertex};
+
+use std::collections::HashMap;
+
+/// The type of primitive that the vertices of a shape make up.
+#[cfg(feature = "glium")]
+pub use self::glium::index::PrimitiveType;
+
+/// The type of primitive that the vertices of a shape make up.
+///
+/// When the `glium` feature is enabled, this is `glium::index::PrimitiveType`
+/// itself. Otherwise, it is a stand-in with the same name for each primitive.
+#[cfg(not(feature = "glium"))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PrimitiveType {
+    Points,
+    LinesList,
+    LineStrip,
+    LineLoop,
+    TrianglesList,
+    TriangleStrip,
+    TriangleFan,
+}
+
+/// Remove duplicate vertices from a list of (unindexed) vertices, returning the
+/// unique vertices along with a list of indices that reproduces the original
+/// list when used to look up the unique vertices.
+///
+/// Vertices are only considered duplicates if their position, normal, and
+/// texture coordinate are bitwise identical, such that the resultant geometry
+/// is identical to the original. Unique vertices are returned in the order in
+/// which they first appear, which helps preserve the locality of the original
+/// primitives for the post-transform vertex cache.
+pub fn deduplicate(vertices: &[Vertex]) -> (Vec<Vertex>, Vec<u32>) {
+    deduplicate_by(vertices, vertices)
+}
+
+/// Deduplicate a list of vertices in the same manner as `deduplicate`, but
+/// return the corresponding elements of `values` (which must be the same length
+/// as `vertices`) for each unique vertex, rather than the vertices themselves.
+pub(crate) fn deduplicate_by<V: Copy>(vertices: &[Vertex], values: &[V]) -> (Vec<V>, Vec<u32>) {
+    let mut unique = Vec::<V>::new();
+    let mut indices = Vec::<u32>::with_capacity(vertices.len());
+    let mut lookup = HashMap::<[u32; 8], u32>::with_capacity(vertices.len());
+
+    for (vertex, value) in vertices.iter().zip(values.iter()) {
+        let index = *lookup.entry(vertex_key(vertex)).or_insert_with(|| {
+            unique.push(*value);
+            (unique.len() - 1) as u32
+        });
+        indices.push(index);
+    }
+
+    (unique, indices)
+}
+
+/// Returns a hashable key for a vertex, built from the bits of its attributes.
+/// Adding zero folds negative zero into positive zero, such that they compare
+/// as equal (as they would when compared as floats).
+fn vertex_key(vertex: &Vertex) -> [u32; 8] {
+    let mut key = [0u32; 8];
+    let values = vertex
+        .position
+        .iter()
+        .chain(vertex.normal.iter())
+        .chain(vertex.texcoord.iter());
+    for (bits, value) in key.iter_mut().zip(values) {
+        *bits = (value + 0.0).to_bits();
+    }
+    key
+}
+
+/// Convert a list of (unindexed) vertices into the given vertex type,
+/// generating tangents when requested or the vertex type requires them, and
+/// colouring each vertex with the colour requested by the builder (if any).
+pub(crate) fn convert<V>(
+    vertices: &[Vertex],
+    options: &ShapeOptions,
+    primitives: PrimitiveType,
+) -> Vec<V>
+where
+    V: FromShapeVertex,
+{
+    let tangents = if options.tangents || V::REQUIRES_TANGENTS {
+        Some(tangent::build_tangents(vertices, options, primitives))
+    } else {
+        None
+    };
+
+    // Undo the transformation of each vertex to find its direction from the
+    // centre of the shape.
+    let inverse = options
+        .matrix
+        .invert()
+        .unwrap_or_else(Matrix4::<f32>::identity);
+
+    vertices
+        .iter()
+        .enumerate()
+        .map(|(index, vertex)| {
+            V::from_shape_vertex(&ShapeVertex {
+                position: vertex.position,
+                normal: vertex.normal,
+                texcoord: vertex.texcoord,
+                tangent: tangents.as_ref().map(|x| x[index]),
+                color: options.color.map(|x| x.of(vertex)),
+                direction: inverse
+                    .transform_point(Point3::from(vertex.position))
+                    .to_vec()
+                    .into(),
+            })
+        })
+        .collect()
+}
+
+/// The vertex types that a shape can be built with by default, as chosen by the
+/// options of its builder.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(not(feature = "glium"), allow(dead_code))]
+pub(crate) enum Layout {
+    Vertex,
+    Tangent,
+    Color,
+    ColorTangent,
+    Cubemap,
+}
+
+/// Returns the vertex type that holds the attributes requested by the given
+/// options, or an error if there is no such vertex type (cubemap texture
+/// coordinates cannot be combined with tangents or colours).
+#[cfg_attr(not(feature = "glium"), allow(dead_code))]
+pub(crate) fn layout(options: &ShapeOptions) -> Result<Layout, ShapeCreationError> {
+    match (options.cubemap, options.tangents, options.color.is_some()) {
+        (true, false, false) => Ok(Layout::Cubemap),
+        (true, _, _) => Err(ShapeCreationError::UnsupportedCubemapAttributes),
+        (false, false, false) => Ok(Layout::Vertex),
+        (false, true, false) => Ok(Layout::Tangent),
+        (false, false, true) => Ok(Layout::Color),
+        (false, true, true) => Ok(Layout::ColorTangent),
+    }
+}
+
+#[cfg(feature = "glium")]
+/// Upload a list of (unindexed) vertices to the GPU, using whichever of the
+/// provided vertex types holds the requested attributes. When the builder is
+/// indexed, the vertices are deduplicated first, and an index buffer is returned
+/// alongside the vertex buffer.
+pub(crate) fn build_buffers<F>(
+    display: &F,
+    vertices: Vec<Vertex>,
+    options: &ShapeOptions,
+    primitives: PrimitiveType,
+) -> Result<
+    (
+        glium::vertex::VertexBufferAny,
+        Option<glium::index::IndexBufferAny>,
+    ),
+    ShapeCreationError,
+>
+where
+    F: glium::backend::Facade,
+{
+    match layout(options)? {
+        Layout::Vertex => build_buffers_as::<F, Vertex>(display, vertices, options, primitives),
+        Layout::Tangent => {
+            build_buffers_as::<F, TangentVertex>(display, vertices, options, primitives)
+        }
+        Layout::Color => build_buffers_as::<F, ColorVertex>(display, vertices, options, primitives),
+        Layout::ColorTangent => {
+            build_buffers_as::<F, ColorTangentVertex>(display, vertices, options, primitives)
+        }
+        Layout::Cubemap => {
+            build_buffers_as::<F, CubemapVertex>(display, vertices, options, primitives)
+        }
+    }
+}
+
+#[cfg(feature = "glium")]
+/// Convert a list of (unindexed) vertices into the given vertex type, and
+/// upload them to the GPU in the same manner as `build_buffers`.
+pub(crate) fn build_buffers_as<F, V>(
+    display: &F,
+    vertices: Vec<Vertex>,
+    options: &ShapeOptions,
+    primitives: PrimitiveType,
+) -> Result<
+    (
+        glium::vertex::VertexBufferAny,
+        Option<glium::index::IndexBufferAny>,
+    ),
+    ShapeCreationError,
+>
+where
+    F: glium::backend::Facade,
+    V: FromShapeVertex + glium::Vertex + Send + 'static,
+{
+    let values = convert::<V>(&vertices, options, primitives);
+    upload(display, &vertices, &values, primitives, options.indexed)
+}
+
+#[cfg(feature = "glium")]
+/// Upload `values` (which correspond to each of the given vertices) to the
+/// GPU, deduplicating them by vertex when `indexed` is set.
+fn upload<F, V>(
+    display: &F,
+    vertices: &[Vertex],
+    values: &[V],
+    primitives: PrimitiveType,
+    indexed: bool,
+) -> Result<
+    (
+        glium::vertex::VertexBufferAny,
+        Option<glium::index::IndexBufferAny>,
+    ),
+    ShapeCreationError,
+>
+where
+    F: glium::backend::Facade,
+    V: glium::Vertex + Send + Copy + 'static,
+{
+    if indexed {
+        let (values, indices) = deduplicate_by(vertices, values);
+        let values = glium::vertex::VertexBuffer::<V>::new(display, &values)?;
+        let indices = glium::index::IndexBuffer::<u32>::new(display, primitives, &indices)?;
+        Ok((values.into(), Some(indices.into())))
+    } else {
+        let values = glium::vertex::VertexBuffer::<V>::new(display, values)?;
+        Ok((values.into(), None))
+    }
+}
+
+/// Returns the vertices of a shape, deduplicating them when `indexed` is set.
+pub(crate) fn vertices(vertices: Vec<Vertex>, indexed: bool) -> Vec<Vertex> {
+    if indexed {
+        deduplicate(&vertices).0
+    } else {
+        vertices
+    }
+}
+
+/// Returns the indices of a shape. When `indexed` is not set, every vertex is
+/// unique, and so the indices simply count up from zero.
+pub(crate) fn indices(vertices: Vec<Vertex>, indexed: bool) -> Vec<u32> {
+    if indexed {
+        deduplicate(&vertices).1
+    } else {
+        (0..vertices.len() as u32).collect()
+    }
+}
+
+/// Returns the vertices of a shape as the given vertex type, deduplicating
+/// them when the builder is indexed.
+pub(crate) fn vertices_as<V>(
+    vertices: Vec<Vertex>,
+    options: &ShapeOptions,
+    primitives: PrimitiveType,
+) -> Vec<V>
+where
+    V: FromShapeVertex,
+{
+    let values = convert::<V>(&vertices, options, primitives);
+    if options.indexed {
+        deduplicate_by(&vertices, &values).0
+    } else {
+        values
+    }
+}
+
+#[cfg(test)]
+use crate::shape::ShapeBuilder;
+
+#[test]
+pub fn ensure_deduplicate_reproduces_original_vertices() {
+    let vertices = crate::cuboid::CuboidBuilder::new()
+        .build_vertices()
+        .expect("Failed to build vertices");
+    let (unique, indices) = deduplicate(&vertices);
+    assert_eq!(indices.len(), vertices.len());
+    for (vertex, index) in vertices.iter().zip(indices.iter()) {
+        let other = unique[*index as usize];
+        assert_eq!(vertex.position, other.position);
+        assert_eq!(vertex.normal, other.normal);
+        assert_eq!(vertex.texcoord, other.texcoord);
+    }
+}
+
+#[test]
+pub fn ensure_deduplicate_shares_identical_vertices() {
+    // Each side of a cuboid has four unique corners.
+    let vertices = crate::cuboid::CuboidBuilder::new()
+        .build_vertices()
+        .expect("Failed to build vertices");
+    let (unique, _) = deduplicate(&vertices);
+    assert_eq!(unique.len(), 6 * 4);
+}
+
+#[test]
+pub fn ensure_deduplicate_treats_signed_zeros_as_equal() {
+    let a = Vertex {
+        position: [0.0, 1.0, 0.0],
+        normal: [0.0, 1.0, 0.0],
+        texcoord: [0.0, 0.0],
+    };
+    let b = Vertex {
+        position: [-0.0, 1.0, 0.0],
+        normal: [0.0, 1.0, -0.0],
+        texcoord: [-0.0, 0.0],
+    };
+    let (unique, indices) = deduplicate(&[a, b]);
+    assert_eq!(unique.len(), 1);
+    assert_eq!(indices, vec![0, 0]);
+}
+
+#[test]
+pub fn ensure_layout_matches_requested_attributes() {
+    let options = crate::cuboid::CuboidBuilder::new();
+    assert_eq!(layout(options.options()).unwrap(), Layout::Vertex);
+    let options = options.with_tangents();
+    assert_eq!(layout(options.options()).unwrap(), Layout::Tangent);
+    let options = options.with_color([1.0, 0.0, 0.0, 1.0]);
+    assert_eq!(layout(options.options()).unwrap(), Layout::ColorTangent);
+    let options = crate::cuboid::CuboidBuilder::new().with_cubemap_texcoords();
+    assert_eq!(layout(options.options()).unwrap(), Layout::Cubemap);
+}
+
+#[test]
+pub fn ensure_cubemap_texcoords_reject_tangents() {
+    let builder = crate::cuboid::CuboidBuilder::new()
+        .with_cubemap_texcoords()
+        .with_tangents();
+    match layout(builder.options()) {
+        Err(ShapeCreationError::UnsupportedCubemapAttributes) => {}
+        _ => panic!("Expected an unsupported cubemap attributes error"),
+    }
+}
+
+#[test]
+pub fn ensure_cubemap_texcoords_reject_colors() {
+    let builder = crate::sphere::SphereBuilder::new()
+        .with_color([1.0, 0.0, 0.0, 1.0])
+        .with_cubemap_texcoords();
+    match layout(builder.options()) {
+        Err(ShapeCreationError::UnsupportedCubemapAttributes) => {}
+        _ => panic!("Expected an unsupported cubemap attributes error"),
+    }
+}
+
+#[test]
+pub fn ensure_cubemap_texcoords_reject_colored_tangents() {
+    let builder = crate::plane::PlaneBuilder::new()
+        .with_cubemap_texcoords()
+        .with_tangents()
+        .with_color([1.0, 0.0, 0.0, 1.0]);
+    match layout(builder.options()) {
+        Err(ShapeCreationError::UnsupportedCubemapAttributes) => {}
+        _ => panic!("Expected an unsupported cubemap attributes error"),
+    }
+}
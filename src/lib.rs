@@ -3,12 +3,17 @@
 //! # Building a shape
 //!
 //! Each shape is constructed using a builder object. The builder objects allow us
-//! to customise the resultant shape.
+//! to customise the resultant shape. All of the builders implement the
+//! `shape::ShapeBuilder` trait, which provides the transformation, build, and
+//! other options shared by every shape (e.g. `indexed`, `with_tangents`, and
+//! `with_color`), and so must be in scope.
 //!
 //! In the following example, we use a `CuboidBuilder` to construct a 2x3x4 `Cuboid`
 //! with its base at the origin:
 //!
 //! ```ignore
+//! use glium_shapes::shape::ShapeBuilder;
+//!
 //! let cuboid = glium_shapes::cuboid::CuboidBuilder::new()
 //!              .translate(0.0, 0.5, 0.0)
 //!              .scale(2.0, 3.0, 4.0)
@@ -27,15 +32,180 @@
 //! ```
 //!
 //! The shader program, uniform buffers, and draw parameters are not provided by this library.
+//!
+//! Every builder produces a `mesh::Mesh` object (each shape type, such as `Cuboid`,
+//! is an alias of it), such that shapes of different kinds can be stored together
+//! and drawn in the same manner:
+//!
+//! ```ignore
+//! let shapes = vec![cuboid, sphere, torus];
+//! for shape in &shapes {
+//!     frame.draw(shape, shape, program, uniforms, params);
+//! }
+//! ```
+//!
+//! # Indexed geometry
+//!
+//! By default, shapes store every vertex of every primitive separately. Calling
+//! `indexed` on a builder instead deduplicates the vertices that are shared between
+//! primitives and stores an index buffer alongside them, which reduces memory usage
+//! and allows the GPU to reuse transformed vertices. Indexed shapes are drawn in
+//! exactly the same way as unindexed shapes:
+//!
+//! ```ignore
+//! let sphere = glium_shapes::sphere::SphereBuilder::new()
+//!              .indexed()
+//!              .build(display)
+//!              .expect("Failed to build sphere shape");
+//! frame.draw(&sphere, &sphere, program, uniforms, params);
+//! ```
+//!
+//! # Normal mapping
+//!
+//! Calling `with_tangents` on a builder generates a tangent for every vertex, such
+//! that the shape can be used with tangent-space normal mapping. The vertices of
+//! the resultant shape use the `vertex::TangentVertex` layout, which adds a `tangent`
+//! attribute (the tangent direction in xyz, and the handedness of the bitangent in w).
+//! The tangents of spheres, quads, and cuboids are exact, as they are derived from
+//! the parameterisation of each shape, while the tangents of other shapes are
+//! accumulated from the texture coordinates of their triangles.
+//!
+//! # Vertex colours
+//!
+//! Calling `with_color` on a builder tints every vertex of the shape, and the
+//! `AxesBuilder` can also colour each axis line separately using `with_axis_colors`.
+//! The vertices of a coloured shape use the `vertex::ColorVertex` layout (or the
+//! `vertex::ColorTangentVertex` layout when tangents are also requested), which adds
+//! an RGBA `color` attribute:
+//!
+//! ```ignore
+//! let axes = glium_shapes::axes::AxesBuilder::new()
+//!            .with_axis_colors([1.0, 0.0, 0.0, 1.0], [0.0, 1.0, 0.0, 1.0], [0.0, 0.0, 1.0, 1.0])
+//!            .build(display)
+//!            .expect("Failed to build axes shape");
+//! ```
+//!
+//! # Skyboxes and interiors
+//!
+//! Calling `flip_faces` (or its alias, `inward_facing`) on a builder turns the
+//! shape inside-out, reversing both the
+//! winding order of its primitives and the direction of its normals, such that it
+//! can be viewed from within. Any builder can also generate cubemap texture
+//! coordinates using `with_cubemap_texcoords`, in which case their
+//! vertices use the `vertex::CubemapVertex` layout, whose three-dimensional
+//! `texcoord` attribute can be used to sample a `glium::texture::Cubemap` (as this
+//! layout has no tangent or colour attributes, building such a shape with
+//! tangents or a colour returns an error):
+//!
+//! ```ignore
+//! let skybox = glium_shapes::cuboid::CuboidBuilder::new()
+//!              .flip_faces()
+//!              .with_cubemap_texcoords()
+//!              .build(display)
+//!              .expect("Failed to build skybox shape");
+//! ```
+//!
+//! # Custom vertex types
+//!
+//! Shapes can also be built using your own vertex structure, by implementing the
+//! `vertex::FromShapeVertex` trait for it and calling `build_as` (or
+//! `build_vertices_as`) on a builder. The trait receives the position, normal,
+//! texture coordinate, and (where available) tangent and colour of each vertex:
+//!
+//! ```ignore
+//! #[derive(Copy, Clone)]
+//! struct MyVertex { position: [f32; 3] }
+//! implement_vertex!(MyVertex, position);
+//!
+//! impl glium_shapes::vertex::FromShapeVertex for MyVertex {
+//!     fn from_shape_vertex(vertex: &glium_shapes::vertex::ShapeVertex) -> Self {
+//!         MyVertex { position: vertex.position }
+//!     }
+//! }
+//!
+//! let cuboid = glium_shapes::cuboid::CuboidBuilder::new()
+//!              .build_as::<MyVertex, _>(display)
+//!              .expect("Failed to build cuboid shape");
+//! ```
+//!
+//! As Rust does not support default type parameters on methods, `build` and
+//! `build_vertices` use `vertex::Vertex` (or the layout requested from the
+//! builder), while `build_as` and `build_vertices_as` use the given type. All of
+//! them are methods of the `shape::ShapeBuilder` trait, so code that is generic over
+//! the builder can request a custom vertex type too:
+//!
+//! ```ignore
+//! fn upload<B: ShapeBuilder>(builder: B, display: &Display) -> Mesh {
+//!     builder.build_as::<MyVertex, _>(display).expect("Failed to build shape")
+//! }
+//! ```
+//!
+//! # CPU-only mesh generation
+//!
+//! Everything that depends on glium (the `build` and `build_as` methods, the
+//! `mesh` module, and the glium vertex implementations) is behind the `glium`
+//! cargo feature, which is enabled by default. Disabling it allows shapes to be
+//! generated on machines without OpenGL (e.g. asset pipelines, or headless
+//! servers), using the `build_vertices` and `build_indices` builder methods.
+//!
+//! # Exporting shapes
+//!
+//! The `export` module writes generated shapes to common 3D file formats, for use
+//! with other tools. For example, to write a sphere to a Wavefront OBJ file:
+//!
+//! ```ignore
+//! let builder = glium_shapes::sphere::SphereBuilder::new().indexed();
+//! let vertices = builder.build_vertices().expect("Failed to build vertices");
+//! let indices = builder.build_indices().expect("Failed to build indices");
+//! let file = std::fs::File::create("sphere.obj").expect("Failed to create file");
+//! glium_shapes::export::obj::write_obj(file, Some("sphere"), &vertices,
+//!                                      Some(&indices), builder.primitives())
+//!     .expect("Failed to write sphere");
+//! ```
+//!
+//! Shapes made up of triangles can also be written to binary or ASCII STL files
+//! (e.g. for 3D printing) using the `export::stl` module.
+//!
+//! To hand shapes to other engines or viewers, the `export::gltf::GltfWriter`
+//! object collects any number of shapes into a glTF 2.0 scene (one node per shape),
+//! and writes it as a `.gltf` file (with a separate `.bin` file) or a `.glb` file.
+//!
+//! For point-cloud and scanning tools, the `export::ply::PlyWriter` object writes
+//! shapes to PLY files, optionally with vertex colours, custom per-vertex float
+//! properties, or only the vertices of each shape.
+//!
+//! The same exporters are available without writing any Rust, through the
+//! `glium-shapes` command-line tool, which is built when the `cli` cargo feature
+//! is enabled (it does not use glium, so the default `glium` feature can be
+//! disabled, e.g. `cargo install glium_shapes --no-default-features --features cli`).
 
 extern crate approx;
 
+#[cfg(feature = "glium")]
 #[macro_use]
 extern crate glium;
 
 pub mod axes;
+pub mod capsule;
+pub mod cone;
+pub mod cubesphere;
 pub mod cuboid;
+pub mod cylinder;
+pub mod disc;
 pub mod errors;
+pub mod export;
+pub mod icosphere;
+pub mod index;
+#[cfg(feature = "glium")]
+pub mod mesh;
+pub mod plane;
+pub mod platonic;
+pub mod pyramid;
 pub mod quad;
+pub mod shading;
+pub mod shape;
 pub mod sphere;
+pub mod tangent;
+pub mod torus;
 pub mod vertex;
+mod winding;
@@ -0,0 +1,69 @@
+//! A module containing the mesh object shared across all shapes.
+
+extern crate glium;
+
+use crate::index;
+
+/// A mesh of vertices and (optionally) indices, as built by any of the shape
+/// builders.
+///
+/// A `Mesh` is a source of both vertices and indices, such that it can be passed
+/// directly to the `glium::Surface::draw` method. As all of the shapes share the
+/// same type, meshes of different shapes can be stored together (e.g. in a
+/// `Vec<Mesh>`) and drawn in the same manner.
+pub struct Mesh {
+    vertices: glium::vertex::VertexBufferAny,
+    indices: Option<glium::index::IndexBufferAny>,
+    primitives: index::PrimitiveType,
+}
+
+impl Mesh {
+    /// Create a new `Mesh` object from a vertex buffer, and an optional index
+    /// buffer. When no index buffer is provided, the vertices are drawn in order
+    /// as the given type of primitive.
+    pub fn new(
+        vertices: glium::vertex::VertexBufferAny,
+        indices: Option<glium::index::IndexBufferAny>,
+        primitives: index::PrimitiveType,
+    ) -> Mesh {
+        Mesh {
+            vertices,
+            indices,
+            primitives,
+        }
+    }
+
+    /// Returns the vertex buffer of the mesh.
+    pub fn vertices(&self) -> &glium::vertex::VertexBufferAny {
+        &self.vertices
+    }
+
+    /// Returns the index buffer of the mesh, if it has one.
+    pub fn indices(&self) -> Option<&glium::index::IndexBufferAny> {
+        self.indices.as_ref()
+    }
+
+    /// Returns the type of primitive that the mesh is made up of.
+    pub fn primitives(&self) -> index::PrimitiveType {
+        self.primitives
+    }
+}
+
+/// Allows a `Mesh` object to be passed as a source of vertices.
+impl<'a> From<&'a Mesh> for glium::vertex::VerticesSource<'a> {
+    fn from(mesh: &'a Mesh) -> glium::vertex::VerticesSource<'a> {
+        (&mesh.vertices).into()
+    }
+}
+
+/// Allows a `Mesh` object to be passed as a source of indices.
+impl<'a> From<&'a Mesh> for glium::index::IndicesSource<'a> {
+    fn from(mesh: &'a Mesh) -> glium::index::IndicesSource<'a> {
+        match mesh.indices {
+            Some(ref indices) => indices.into(),
+            None => glium::index::IndicesSource::NoIndices {
+                primitives: mesh.primitives,
+            },
+        }
+    }
+}
@@ -0,0 +1,300 @@
+//! A module for constructing subdivided plane (grid) shapes.
+
+extern crate cgmath;
+#[cfg(feature = "glium")]
+extern crate glium;
+
+use self::cgmath::*;
+use crate::errors::ShapeCreationError;
+use crate::index;
+#[cfg(feature = "glium")]
+use crate::mesh::Mesh;
+use crate::shape::{ShapeBuilder, ShapeOptions};
+use crate::vertex::Vertex;
+
+/// A polygonal `Plane` object.
+///
+/// This object is constructed using a `PlaneBuilder` object.
+#[cfg(feature = "glium")]
+pub type Plane = Mesh;
+
+/// Responsible for building and returning a `Plane` object.
+///
+/// A plane is a quad that is subdivided into a grid of cells, which makes it
+/// suitable for vertex displacement, terrain, cloth, and similar effects. By
+/// default, the resultant plane has the same size, position, and alignment as
+/// the default quad built by a `QuadBuilder` - a regular quad of length 2 on
+/// each side, with its centre at the origin, and aligned to face the negative
+/// Z-axis. The defaults can be overridden using the transformation methods on
+/// this object.
+///
+/// The resultant geometry is a list of triangles (which should usually be
+/// `indexed`, such that neighbouring cells share their vertices), constructed to
+/// suit OpenGL defaults - assuming a right-handed coordinate system, front-facing polygons are defined in
+/// counter-clock-wise order. Vertex normals point in the direction of the face.
+/// Vertex texture coordinates define a planar-projection on the face. The
+/// `flip_faces` method makes the plane face the other way (e.g. for the ceiling
+/// of a room).
+pub struct PlaneBuilder {
+    options: ShapeOptions,
+    u_divisions: usize,
+    v_divisions: usize,
+}
+
+impl Default for PlaneBuilder {
+    fn default() -> PlaneBuilder {
+        PlaneBuilder {
+            options: ShapeOptions::default(),
+            u_divisions: 8,
+            v_divisions: 8,
+        }
+    }
+}
+
+impl PlaneBuilder {
+    /// Create a new `PlaneBuilder` object.
+    pub fn new() -> PlaneBuilder {
+        Default::default()
+    }
+
+    /// Specify the number of divisions to make in the u direction (along the
+    /// x-axis), and v direction (along the y-axis). By default, the builder
+    /// will use 8 divisions in both axes.
+    pub fn with_divisions(mut self, u: usize, v: usize) -> Self {
+        self.u_divisions = u;
+        self.v_divisions = v;
+        self
+    }
+
+    /// Ensure there are enough divisions in u and v to produce valid geometry.
+    fn validate(&self) -> Result<(), ShapeCreationError> {
+        if self.u_divisions < 1 {
+            return Err(ShapeCreationError::NotEnoughDivisionsInU);
+        }
+
+        if self.v_divisions < 1 {
+            return Err(ShapeCreationError::NotEnoughDivisionsInV);
+        }
+
+        Ok(())
+    }
+
+    /// Build the unique vertices of the grid, column by column.
+    fn build_grid_vertices(&self) -> Result<Vec<Vertex>, ShapeCreationError> {
+        self.validate()?;
+
+        // Compute the normal transformation matrix.
+        let normal_matrix = self.options.normal_matrix();
+
+        // Build the vertices.
+        let normal = (normal_matrix * Vector3::<f32>::new(0.0, 0.0, -1.0)).normalize();
+        let mut vertices =
+            Vec::<Vertex>::with_capacity((self.u_divisions + 1) * (self.v_divisions + 1));
+        for i in 0..=self.u_divisions {
+            for j in 0..=self.v_divisions {
+                let (u, v) = (
+                    i as f32 / self.u_divisions as f32,
+                    j as f32 / self.v_divisions as f32,
+                );
+                let position = Vector4::<f32>::new((u * 2.0) - 1.0, (v * 2.0) - 1.0, 0.0, 1.0);
+                vertices.push(Vertex {
+                    position: Point3::<f32>::from_homogeneous(self.options.matrix * position)
+                        .into(),
+                    normal: normal.into(),
+                    texcoord: [u, v],
+                });
+            }
+        }
+        Ok(vertices)
+    }
+
+    /// Build the indices of the triangles of the grid, in counter-clock-wise order
+    /// before any transformation.
+    fn build_grid_indices(&self) -> Result<Vec<u32>, ShapeCreationError> {
+        self.validate()?;
+
+        // Each cell is split into two triangles, matching the triangles of a `Quad`.
+        let stride = self.v_divisions + 1;
+        let mut indices = Vec::<u32>::with_capacity(self.num_unindexed_vertices());
+        for i in 0..self.u_divisions {
+            for j in 0..self.v_divisions {
+                let corners = [
+                    i * stride + j,
+                    i * stride + j + 1,
+                    (i + 1) * stride + j,
+                    (i + 1) * stride + j + 1,
+                ];
+                for corner in &[0, 1, 2, 2, 1, 3] {
+                    indices.push(corners[*corner] as u32);
+                }
+            }
+        }
+        Ok(indices)
+    }
+}
+
+impl ShapeBuilder for PlaneBuilder {
+    fn options(&self) -> &ShapeOptions {
+        &self.options
+    }
+
+    fn options_mut(&mut self) -> &mut ShapeOptions {
+        &mut self.options
+    }
+
+    fn primitives(&self) -> index::PrimitiveType {
+        index::PrimitiveType::TrianglesList
+    }
+
+    fn build_unindexed_vertices(&self) -> Result<Vec<Vertex>, ShapeCreationError> {
+        let vertices = self.build_grid_vertices()?;
+        Ok(self
+            .build_grid_indices()?
+            .iter()
+            .map(|x| vertices[*x as usize])
+            .collect())
+    }
+
+    fn num_unindexed_vertices(&self) -> usize {
+        self.u_divisions * self.v_divisions * 6
+    }
+}
+
+#[test]
+pub fn ensure_default_plane_has_edge_lengths_of_two() {
+    use std::f32;
+    let vertices = PlaneBuilder::new()
+        .build_vertices()
+        .expect("Failed to build vertices");
+    let mut min = Vector3::<f32>::new(f32::MAX, f32::MAX, f32::MAX);
+    let mut max = -min;
+    for vertex in &vertices {
+        let pos = Vector3::<f32>::from(vertex.position);
+        min.x = f32::min(min.x, pos.x);
+        min.y = f32::min(min.y, pos.y);
+        min.z = f32::min(min.z, pos.z);
+        max.x = f32::max(max.x, pos.x);
+        max.y = f32::max(max.y, pos.y);
+        max.z = f32::max(max.z, pos.z);
+    }
+    assert_eq!(min, Vector3::new(-1.0, -1.0, 0.0));
+    assert_eq!(max, Vector3::new(1.0, 1.0, 0.0));
+}
+
+#[test]
+pub fn ensure_default_plane_has_centroid_at_origin() {
+    let vertices = PlaneBuilder::new()
+        .build_vertices()
+        .expect("Failed to build vertices");
+    let mut sum = Vector3::<f32>::zero();
+    for vertex in &vertices {
+        sum += Vector3::<f32>::from(vertex.position);
+    }
+    assert_ulps_eq!(sum, Vector3::<f32>::zero(), epsilon = 0.0001);
+}
+
+#[test]
+pub fn ensure_default_plane_has_ccw_triangles() {
+    let builder = PlaneBuilder::new();
+    let vertices = builder.build_vertices().expect("Failed to build vertices");
+    let indices = builder.build_indices().expect("Failed to build indices");
+    for tri in indices.chunks(3) {
+        let v0 = Vector3::<f32>::from(vertices[tri[0] as usize].position);
+        let v1 = Vector3::<f32>::from(vertices[tri[1] as usize].position);
+        let v2 = Vector3::<f32>::from(vertices[tri[2] as usize].position);
+        let eyepos = v0 + Vector3::<f32>::from(vertices[tri[0] as usize].normal);
+        let e0 = v1 - v0;
+        let e1 = v2 - v0;
+        let n = e0.cross(e1);
+        assert!(n.dot(v0 - eyepos) <= 0.0);
+        assert!(n.dot(v1 - eyepos) <= 0.0);
+        assert!(n.dot(v2 - eyepos) <= 0.0);
+    }
+}
+
+#[test]
+pub fn ensure_default_plane_has_face_aligned_normals() {
+    let vertices = PlaneBuilder::new()
+        .build_vertices()
+        .expect("Failed to build vertices");
+    for vertex in vertices.iter() {
+        let vnormal = Vector3::<f32>::from(vertex.normal);
+        assert_eq!(vnormal, Vector3::<f32>::new(0.0, 0.0, -1.0));
+    }
+}
+
+#[test]
+pub fn ensure_plane_uvs_are_in_correct_range() {
+    use std::f32;
+    let vertices = PlaneBuilder::new()
+        .build_vertices()
+        .expect("Failed to build vertices");
+    let mut min = Vector2::<f32>::new(f32::MAX, f32::MAX);
+    let mut max = -min;
+    for vertex in &vertices {
+        min.x = f32::min(min.x, vertex.texcoord[0]);
+        min.y = f32::min(min.y, vertex.texcoord[1]);
+        max.x = f32::max(max.x, vertex.texcoord[0]);
+        max.y = f32::max(max.y, vertex.texcoord[1]);
+    }
+    assert!(min == Vector2::<f32>::zero());
+    assert!(max == Vector2::<f32>::from_value(1.0));
+}
+
+#[test]
+pub fn ensure_undivided_plane_matches_quad() {
+    let builder = PlaneBuilder::new()
+        .with_divisions(1, 1)
+        .rotate_x(0.5)
+        .indexed();
+    let plane = builder.build_vertices().expect("Failed to build vertices");
+    let quad = crate::quad::QuadBuilder::new()
+        .rotate_x(0.5)
+        .build_vertices()
+        .expect("Failed to build vertices");
+    assert_eq!(plane.len(), quad.len());
+    for (a, b) in plane.iter().zip(quad.iter()) {
+        assert_eq!(a.position, b.position);
+        assert_eq!(a.normal, b.normal);
+        assert_eq!(a.texcoord, b.texcoord);
+    }
+    assert_eq!(
+        builder.build_indices().expect("Failed to build indices"),
+        vec![0, 1, 2, 2, 1, 3]
+    );
+}
+
+#[test]
+pub fn ensure_plane_indices_are_in_range() {
+    let builder = PlaneBuilder::new().with_divisions(5, 3).indexed();
+    let vertices = builder.build_vertices().expect("Failed to build vertices");
+    let indices = builder.build_indices().expect("Failed to build indices");
+    assert_eq!(vertices.len(), 6 * 4);
+    assert_eq!(indices.len(), 5 * 3 * 6);
+    assert!(indices
+        .iter()
+        .all(|index| (*index as usize) < vertices.len()));
+}
+
+#[test]
+pub fn ensure_plane_tangents_match_quad_tangents() {
+    let vertices = PlaneBuilder::new()
+        .indexed()
+        .build_tangent_vertices()
+        .expect("Failed to build vertices");
+    assert_eq!(vertices.len(), 9 * 9);
+    for vertex in &vertices {
+        assert_eq!(vertex.tangent, [1.0, 0.0, 0.0, -1.0]);
+    }
+}
+
+#[test]
+pub fn ensure_unindexed_plane_has_separate_triangles() {
+    let builder = PlaneBuilder::new().with_divisions(5, 3);
+    let vertices = builder.build_vertices().expect("Failed to build vertices");
+    assert_eq!(vertices.len(), 5 * 3 * 6);
+    assert_eq!(
+        builder.build_indices().expect("Failed to build indices"),
+        (0..vertices.len() as u32).collect::<Vec<u32>>()
+    );
+}
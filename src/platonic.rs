@@ -0,0 +1,561 @@
+//! A module for constructing platonic solid shapes.
+
+extern crate cgmath;
+#[cfg(feature = "glium")]
+extern crate glium;
+
+use self::cgmath::*;
+use crate::errors::ShapeCreationError;
+use crate::index;
+#[cfg(feature = "glium")]
+use crate::mesh::Mesh;
+use crate::shape::{ShapeBuilder, ShapeOptions};
+use crate::vertex::Vertex;
+
+use std::f32;
+
+/// A polygonal `Platonic` object.
+///
+/// This object is constructed using a `PlatonicBuilder` object.
+#[cfg(feature = "glium")]
+pub type Platonic = Mesh;
+
+/// The five platonic solids that can be built by a `PlatonicBuilder`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum PlatonicSolid {
+    /// A solid with 4 triangular faces.
+    #[default]
+    Tetrahedron,
+
+    /// A solid with 6 square faces.
+    Cube,
+
+    /// A solid with 8 triangular faces.
+    Octahedron,
+
+    /// A solid with 12 pentagonal faces.
+    Dodecahedron,
+
+    /// A solid with 20 triangular faces.
+    Icosahedron,
+}
+
+/// The golden ratio.
+pub(crate) const PHI: f32 = 1.618_034;
+
+/// The reciprocal of the golden ratio.
+const INV_PHI: f32 = PHI - 1.0;
+
+/// The vertices of a tetrahedron, before they are projected onto the unit-sphere.
+const TETRAHEDRON_VERTICES: [[f32; 3]; 4] = [
+    [1.0, 1.0, 1.0],
+    [1.0, -1.0, -1.0],
+    [-1.0, 1.0, -1.0],
+    [-1.0, -1.0, 1.0],
+];
+
+/// The counter-clock-wise faces of a tetrahedron.
+const TETRAHEDRON_FACES: [[usize; 3]; 4] = [[2, 0, 1], [1, 0, 3], [3, 0, 2], [2, 1, 3]];
+
+/// The vertices of a cube, before they are projected onto the unit-sphere.
+/// These follow the same corner encoding as the `cuboid` module.
+const CUBE_VERTICES: [[f32; 3]; 8] = [
+    [-1.0, -1.0, -1.0],
+    [-1.0, 1.0, -1.0],
+    [1.0, -1.0, -1.0],
+    [1.0, 1.0, -1.0],
+    [-1.0, -1.0, 1.0],
+    [-1.0, 1.0, 1.0],
+    [1.0, -1.0, 1.0],
+    [1.0, 1.0, 1.0],
+];
+
+/// The counter-clock-wise faces of a cube, in the same order (and starting
+/// from the same corners) as the sides of a `CuboidBuilder`, such that both
+/// shapes share the same texture coordinates.
+const CUBE_FACES: [[usize; 4]; 6] = [
+    [0, 4, 5, 1],
+    [6, 2, 3, 7],
+    [0, 2, 6, 4],
+    [5, 7, 3, 1],
+    [2, 0, 1, 3],
+    [4, 6, 7, 5],
+];
+
+/// The vertices of an octahedron.
+const OCTAHEDRON_VERTICES: [[f32; 3]; 6] = [
+    [1.0, 0.0, 0.0],
+    [-1.0, 0.0, 0.0],
+    [0.0, 1.0, 0.0],
+    [0.0, -1.0, 0.0],
+    [0.0, 0.0, 1.0],
+    [0.0, 0.0, -1.0],
+];
+
+/// The counter-clock-wise faces of an octahedron.
+const OCTAHEDRON_FACES: [[usize; 3]; 8] = [
+    [4, 0, 2],
+    [2, 0, 5],
+    [3, 0, 4],
+    [5, 0, 3],
+    [2, 1, 4],
+    [5, 1, 2],
+    [4, 1, 3],
+    [3, 1, 5],
+];
+
+/// The vertices of a dodecahedron, before they are projected onto the unit-sphere.
+const DODECAHEDRON_VERTICES: [[f32; 3]; 20] = [
+    [-1.0, -1.0, -1.0],
+    [-1.0, -1.0, 1.0],
+    [-1.0, 1.0, -1.0],
+    [-1.0, 1.0, 1.0],
+    [1.0, -1.0, -1.0],
+    [1.0, -1.0, 1.0],
+    [1.0, 1.0, -1.0],
+    [1.0, 1.0, 1.0],
+    [0.0, -INV_PHI, -PHI],
+    [0.0, -INV_PHI, PHI],
+    [0.0, INV_PHI, -PHI],
+    [0.0, INV_PHI, PHI],
+    [-INV_PHI, -PHI, 0.0],
+    [-INV_PHI, PHI, 0.0],
+    [INV_PHI, -PHI, 0.0],
+    [INV_PHI, PHI, 0.0],
+    [-PHI, 0.0, -INV_PHI],
+    [-PHI, 0.0, INV_PHI],
+    [PHI, 0.0, -INV_PHI],
+    [PHI, 0.0, INV_PHI],
+];
+
+/// The counter-clock-wise faces of a dodecahedron.
+const DODECAHEDRON_FACES: [[usize; 5]; 12] = [
+    [17, 16, 0, 12, 1],
+    [10, 8, 0, 16, 2],
+    [14, 12, 0, 8, 4],
+    [3, 17, 1, 9, 11],
+    [5, 9, 1, 12, 14],
+    [3, 13, 2, 16, 17],
+    [6, 10, 2, 13, 15],
+    [15, 13, 3, 11, 7],
+    [5, 14, 4, 18, 19],
+    [6, 18, 4, 8, 10],
+    [11, 9, 5, 19, 7],
+    [19, 18, 6, 15, 7],
+];
+
+/// The vertices of an icosahedron, before they are projected onto the unit-sphere.
+pub(crate) const ICOSAHEDRON_VERTICES: [[f32; 3]; 12] = [
+    [-1.0, PHI, 0.0],
+    [1.0, PHI, 0.0],
+    [-1.0, -PHI, 0.0],
+    [1.0, -PHI, 0.0],
+    [0.0, -1.0, PHI],
+    [0.0, 1.0, PHI],
+    [0.0, -1.0, -PHI],
+    [0.0, 1.0, -PHI],
+    [PHI, 0.0, -1.0],
+    [PHI, 0.0, 1.0],
+    [-PHI, 0.0, -1.0],
+    [-PHI, 0.0, 1.0],
+];
+
+/// The counter-clock-wise faces of an icosahedron.
+pub(crate) const ICOSAHEDRON_FACES: [[usize; 3]; 20] = [
+    [0, 11, 5],
+    [0, 5, 1],
+    [0, 1, 7],
+    [0, 7, 10],
+    [0, 10, 11],
+    [1, 5, 9],
+    [5, 11, 4],
+    [11, 10, 2],
+    [10, 7, 6],
+    [7, 1, 8],
+    [3, 9, 4],
+    [3, 4, 2],
+    [3, 2, 6],
+    [3, 6, 8],
+    [3, 8, 9],
+    [4, 9, 5],
+    [2, 4, 11],
+    [6, 2, 10],
+    [8, 6, 7],
+    [9, 8, 1],
+];
+
+impl PlatonicSolid {
+    /// Returns the vertices of the solid, before they are projected onto the
+    /// unit-sphere.
+    fn vertices(self) -> &'static [[f32; 3]] {
+        match self {
+            PlatonicSolid::Tetrahedron => &TETRAHEDRON_VERTICES,
+            PlatonicSolid::Cube => &CUBE_VERTICES,
+            PlatonicSolid::Octahedron => &OCTAHEDRON_VERTICES,
+            PlatonicSolid::Dodecahedron => &DODECAHEDRON_VERTICES,
+            PlatonicSolid::Icosahedron => &ICOSAHEDRON_VERTICES,
+        }
+    }
+
+    /// Returns the counter-clock-wise faces of the solid, as indices into the
+    /// array returned by `vertices`.
+    fn faces(self) -> Vec<&'static [usize]> {
+        match self {
+            PlatonicSolid::Tetrahedron => TETRAHEDRON_FACES.iter().map(|x| &x[..]).collect(),
+            PlatonicSolid::Cube => CUBE_FACES.iter().map(|x| &x[..]).collect(),
+            PlatonicSolid::Octahedron => OCTAHEDRON_FACES.iter().map(|x| &x[..]).collect(),
+            PlatonicSolid::Dodecahedron => DODECAHEDRON_FACES.iter().map(|x| &x[..]).collect(),
+            PlatonicSolid::Icosahedron => ICOSAHEDRON_FACES.iter().map(|x| &x[..]).collect(),
+        }
+    }
+
+    /// Returns the number of faces of the solid.
+    fn num_faces(self) -> usize {
+        match self {
+            PlatonicSolid::Tetrahedron => 4,
+            PlatonicSolid::Cube => 6,
+            PlatonicSolid::Octahedron => 8,
+            PlatonicSolid::Dodecahedron => 12,
+            PlatonicSolid::Icosahedron => 20,
+        }
+    }
+
+    /// Returns the number of vertices around each face of the solid.
+    fn num_vertices_per_face(self) -> usize {
+        match self {
+            PlatonicSolid::Cube => 4,
+            PlatonicSolid::Dodecahedron => 5,
+            _ => 3,
+        }
+    }
+}
+
+/// Responsible for building and returning a `Platonic` object.
+///
+/// By default, the builder produces a tetrahedron, although any of the five
+/// platonic solids can be selected using `with_solid` (or by constructing the
+/// builder with one of the named constructors, such as `dodecahedron`). Every
+/// solid is scaled to have a circumradius of 1 (e.g. all of its vertices lie
+/// on the unit-sphere), with its centre-of-mass located at the origin. This
+/// can be overriden using the transformation methods on this object.
+///
+/// The resultant geometry is constructed to suit OpenGL defaults - assuming
+/// a right-handed coordinate system, front-facing polygons are defined in
+/// counter-clock-wise order. Vertex normals point in the direction of their
+/// respective face (such that the shape appears faceted when lit). Vertex
+/// texture coordinates define a planar-projection on each face, such that the
+/// face fills the 0 to 1 range in both axes. The faces of a cube share the
+/// same texture coordinates as those of a `CuboidBuilder`.
+pub struct PlatonicBuilder {
+    options: ShapeOptions,
+    solid: PlatonicSolid,
+}
+
+impl Default for PlatonicBuilder {
+    fn default() -> Self {
+        PlatonicBuilder {
+            options: ShapeOptions::default(),
+            solid: PlatonicSolid::Tetrahedron,
+        }
+    }
+}
+
+impl PlatonicBuilder {
+    /// Create a new `PlatonicBuilder` object.
+    pub fn new() -> PlatonicBuilder {
+        Default::default()
+    }
+
+    /// Create a new `PlatonicBuilder` object that builds a tetrahedron.
+    pub fn tetrahedron() -> PlatonicBuilder {
+        PlatonicBuilder::new().with_solid(PlatonicSolid::Tetrahedron)
+    }
+
+    /// Create a new `PlatonicBuilder` object that builds a cube.
+    pub fn cube() -> PlatonicBuilder {
+        PlatonicBuilder::new().with_solid(PlatonicSolid::Cube)
+    }
+
+    /// Create a new `PlatonicBuilder` object that builds an octahedron.
+    pub fn octahedron() -> PlatonicBuilder {
+        PlatonicBuilder::new().with_solid(PlatonicSolid::Octahedron)
+    }
+
+    /// Create a new `PlatonicBuilder` object that builds a dodecahedron.
+    pub fn dodecahedron() -> PlatonicBuilder {
+        PlatonicBuilder::new().with_solid(PlatonicSolid::Dodecahedron)
+    }
+
+    /// Create a new `PlatonicBuilder` object that builds an icosahedron.
+    pub fn icosahedron() -> PlatonicBuilder {
+        PlatonicBuilder::new().with_solid(PlatonicSolid::Icosahedron)
+    }
+
+    /// Specify which platonic solid to build. By default, the builder will
+    /// build a `PlatonicSolid::Tetrahedron`.
+    pub fn with_solid(mut self, solid: PlatonicSolid) -> Self {
+        self.solid = solid;
+        self
+    }
+
+    /// Returns the number of polygonal faces of the resultant solid.
+    pub fn num_faces(&self) -> usize {
+        self.solid.num_faces()
+    }
+
+    /// Returns the number of vertices emitted for each face of the resultant
+    /// solid, once it has been triangulated.
+    pub fn num_vertices_per_face(&self) -> usize {
+        (self.solid.num_vertices_per_face() - 2) * 3
+    }
+}
+
+impl ShapeBuilder for PlatonicBuilder {
+    fn options(&self) -> &ShapeOptions {
+        &self.options
+    }
+
+    fn options_mut(&mut self) -> &mut ShapeOptions {
+        &mut self.options
+    }
+
+    fn primitives(&self) -> index::PrimitiveType {
+        index::PrimitiveType::TrianglesList
+    }
+
+    fn build_unindexed_vertices(&self) -> Result<Vec<Vertex>, ShapeCreationError> {
+        // Project the vertices of the solid onto the unit-sphere.
+        let positions = self
+            .solid
+            .vertices()
+            .iter()
+            .map(|x| Vector3::<f32>::from(*x).normalize())
+            .collect::<Vec<Vector3<f32>>>();
+
+        // Compute the normal transformation matrix.
+        let normal_matrix = self.options.normal_matrix();
+
+        // Build vertex array.
+        let total_num_verts = self.num_unindexed_vertices();
+        let mut vertices = Vec::<Vertex>::with_capacity(total_num_verts);
+
+        let mut emit = |vpos: Vector3<f32>, normal: Vector3<f32>, texcoord: [f32; 2]| {
+            let pos = self.options.matrix * vpos.extend(1.0);
+            vertices.push(Vertex {
+                position: Point3::<f32>::from_homogeneous(pos).into(),
+                normal: (normal_matrix * normal).normalize().into(),
+                texcoord,
+            });
+        };
+
+        for face in self.solid.faces() {
+            let corners = face.iter().map(|x| positions[*x]).collect::<Vec<_>>();
+            build_polygon(&corners, &mut emit);
+        }
+
+        assert!(vertices.len() == total_num_verts);
+        Ok(vertices)
+    }
+
+    fn num_unindexed_vertices(&self) -> usize {
+        self.num_faces() * self.num_vertices_per_face()
+    }
+}
+
+/// Triangulate a convex, planar polygon whose corners are given in
+/// counter-clock-wise order, calling `emit` with the position, face normal,
+/// and texture coordinate of each resultant triangle vertex.
+///
+/// The polygon is triangulated as a fan about its first corner. Texture
+/// coordinates define a planar-projection on the face, where the u axis runs
+/// along the first edge, and are fitted to the 0 to 1 range in both axes.
+pub(crate) fn build_polygon<F>(corners: &[Vector3<f32>], mut emit: F)
+where
+    F: FnMut(Vector3<f32>, Vector3<f32>, [f32; 2]),
+{
+    // Compute the face normal, and a basis for the plane of the face.
+    let face_normal = (corners[1] - corners[0])
+        .cross(corners[2] - corners[0])
+        .normalize();
+    let u_axis = (corners[1] - corners[0]).normalize();
+    let v_axis = face_normal.cross(u_axis);
+
+    // Project the corners onto the plane, and fit them to the 0 to 1
+    // texture coordinate range.
+    let planar = corners
+        .iter()
+        .map(|x| Vector2::<f32>::new(x.dot(u_axis), x.dot(v_axis)))
+        .collect::<Vec<_>>();
+    let min = planar
+        .iter()
+        .fold(Vector2::<f32>::from_value(f32::MAX), |a, b| {
+            Vector2::<f32>::new(a.x.min(b.x), a.y.min(b.y))
+        });
+    let max = planar
+        .iter()
+        .fold(Vector2::<f32>::from_value(f32::MIN), |a, b| {
+            Vector2::<f32>::new(a.x.max(b.x), a.y.max(b.y))
+        });
+
+    // Emit a fan of triangles about the first corner.
+    for triangle in 1..corners.len() - 1 {
+        for &corner in &[0, triangle, triangle + 1] {
+            let texcoord = (planar[corner] - min).div_element_wise(max - min);
+            emit(corners[corner], face_normal, texcoord.into());
+        }
+    }
+}
+
+#[cfg(test)]
+const ALL_SOLIDS: [PlatonicSolid; 5] = [
+    PlatonicSolid::Tetrahedron,
+    PlatonicSolid::Cube,
+    PlatonicSolid::Octahedron,
+    PlatonicSolid::Dodecahedron,
+    PlatonicSolid::Icosahedron,
+];
+
+#[test]
+pub fn ensure_default_platonic_solids_have_unit_circumradius() {
+    for solid in &ALL_SOLIDS {
+        let vertices = PlatonicBuilder::new()
+            .with_solid(*solid)
+            .build_vertices()
+            .expect("Failed to build vertices");
+        for vertex in &vertices {
+            assert_ulps_eq!(Vector3::<f32>::from(vertex.position).magnitude(), 1.0);
+        }
+    }
+}
+
+#[test]
+pub fn ensure_default_platonic_solids_have_centroid_at_origin() {
+    for solid in &ALL_SOLIDS {
+        let vertices = PlatonicBuilder::new()
+            .with_solid(*solid)
+            .build_vertices()
+            .expect("Failed to build vertices");
+        // Faces are triangulated as fans, so only count each corner once.
+        let mut corners = Vec::<Vector3<f32>>::new();
+        for vertex in &vertices {
+            let pos = Vector3::<f32>::from(vertex.position);
+            if !corners
+                .iter()
+                .any(|x| abs_diff_eq!(*x, pos, epsilon = 1e-6))
+            {
+                corners.push(pos);
+            }
+        }
+        assert_eq!(corners.len(), solid.vertices().len());
+        let sum = corners.iter().fold(Vector3::<f32>::zero(), |a, b| a + b);
+        assert_ulps_eq!(sum, Vector3::<f32>::zero(), epsilon = 0.0001);
+    }
+}
+
+#[test]
+pub fn ensure_default_platonic_solids_have_outward_facing_normals() {
+    for solid in &ALL_SOLIDS {
+        let vertices = PlatonicBuilder::new()
+            .with_solid(*solid)
+            .scale(2.0, 2.0, 2.0)
+            .build_vertices()
+            .expect("Failed to build vertices");
+        for vertex in &vertices {
+            let position = Vector3::<f32>::from(vertex.position);
+            let normal = Vector3::<f32>::from(vertex.normal);
+            assert!(position.dot(normal) > 0.0);
+        }
+    }
+}
+
+#[test]
+pub fn ensure_default_platonic_solids_have_ccw_triangles() {
+    for solid in &ALL_SOLIDS {
+        let vertices = PlatonicBuilder::new()
+            .with_solid(*solid)
+            .build_vertices()
+            .expect("Failed to build vertices");
+        for chunk in vertices.chunks(3) {
+            let v0 = Vector3::<f32>::from(chunk[0].position);
+            let v1 = Vector3::<f32>::from(chunk[1].position);
+            let v2 = Vector3::<f32>::from(chunk[2].position);
+            let eyepos = v0 + Vector3::<f32>::from(chunk[0].normal);
+            let e0 = v1 - v0;
+            let e1 = v2 - v0;
+            let n = e0.cross(e1);
+            assert!(n.dot(v0 - eyepos) <= 0.0);
+            assert!(n.dot(v1 - eyepos) <= 0.0);
+            assert!(n.dot(v2 - eyepos) <= 0.0);
+        }
+    }
+}
+
+#[test]
+pub fn ensure_default_platonic_solids_have_faceted_normals() {
+    for solid in &ALL_SOLIDS {
+        let builder = PlatonicBuilder::new().with_solid(*solid);
+        let vertices = builder.build_vertices().expect("Failed to build vertices");
+        for face in vertices.chunks(builder.num_vertices_per_face()) {
+            // Every corner of a face (not just each triangle) must be coplanar.
+            let v0 = Vector3::<f32>::from(face[0].position);
+            let n0 = Vector3::<f32>::from(face[0].normal);
+            for vertex in face {
+                let pos = Vector3::<f32>::from(vertex.position);
+                assert_ulps_eq!(Vector3::<f32>::from(vertex.normal), n0);
+                assert!(abs_diff_eq!((pos - v0).dot(n0), 0.0, epsilon = 1e-6));
+            }
+        }
+    }
+}
+
+#[test]
+pub fn ensure_platonic_solids_have_per_face_uvs_in_unit_range() {
+    for solid in &ALL_SOLIDS {
+        let builder = PlatonicBuilder::new().with_solid(*solid);
+        let vertices = builder.build_vertices().expect("Failed to build vertices");
+        for face in vertices.chunks(builder.num_vertices_per_face()) {
+            let min_u = face.iter().map(|x| x.texcoord[0]).fold(f32::MAX, f32::min);
+            let max_u = face.iter().map(|x| x.texcoord[0]).fold(f32::MIN, f32::max);
+            let min_v = face.iter().map(|x| x.texcoord[1]).fold(f32::MAX, f32::min);
+            let max_v = face.iter().map(|x| x.texcoord[1]).fold(f32::MIN, f32::max);
+            assert_ulps_eq!(min_u, 0.0);
+            assert_ulps_eq!(max_u, 1.0);
+            assert_ulps_eq!(min_v, 0.0);
+            assert_ulps_eq!(max_v, 1.0);
+        }
+    }
+}
+
+#[test]
+pub fn ensure_platonic_cube_matches_cuboid_texcoords() {
+    let cuboid = crate::cuboid::CuboidBuilder::new()
+        .build_vertices()
+        .expect("Failed to build vertices");
+    let cube = PlatonicBuilder::cube()
+        .build_vertices()
+        .expect("Failed to build vertices");
+    for a in &cuboid {
+        let position = Vector3::<f32>::from(a.position).normalize();
+        let normal = Vector3::<f32>::from(a.normal);
+        let b = cube
+            .iter()
+            .find(|x| {
+                abs_diff_eq!(Vector3::<f32>::from(x.position), position, epsilon = 1e-6)
+                    && abs_diff_eq!(Vector3::<f32>::from(x.normal), normal, epsilon = 1e-6)
+            })
+            .expect("Failed to find matching vertex");
+        assert_ulps_eq!(b.texcoord[0], a.texcoord[0]);
+        assert_ulps_eq!(b.texcoord[1], a.texcoord[1]);
+    }
+}
+
+#[test]
+pub fn ensure_platonic_solids_have_expected_number_of_vertices() {
+    let expected = [4 * 3, 6 * 6, 8 * 3, 12 * 9, 20 * 3];
+    for (solid, count) in ALL_SOLIDS.iter().zip(expected.iter()) {
+        let builder = PlatonicBuilder::new().with_solid(*solid);
+        let vertices = builder.build_vertices().expect("Failed to build vertices");
+        assert_eq!(vertices.len(), *count);
+        assert_eq!(builder.num_vertices(), *count);
+    }
+}
@@ -0,0 +1,312 @@
+//! A module for constructing pyramid shapes.
+
+extern crate cgmath;
+#[cfg(feature = "glium")]
+extern crate glium;
+
+use self::cgmath::*;
+use crate::errors::ShapeCreationError;
+use crate::index;
+#[cfg(feature = "glium")]
+use crate::mesh::Mesh;
+use crate::platonic::build_polygon;
+use crate::shape::{ShapeBuilder, ShapeOptions};
+use crate::vertex::Vertex;
+
+use std::f32;
+
+/// A polygonal `Pyramid` object.
+///
+/// This object is constructed using a `PyramidBuilder` object.
+#[cfg(feature = "glium")]
+pub type Pyramid = Mesh;
+
+/// Responsible for building and returning a `Pyramid` object.
+///
+/// By default, the pyramid has a square base inscribed in a circle of radius 1,
+/// with its sides aligned to the x and z axes, and a height of 2, such that its
+/// base is located at y = -1 and its apex is located at y = 1. The base may be
+/// any regular polygon, and the apex may be offset from the axis of the base to
+/// produce an oblique pyramid. This can be overriden using the transformation
+/// methods on this object.
+///
+/// The resultant geometry is constructed to suit OpenGL defaults - assuming
+/// a right-handed coordinate system, front-facing polygons are defined in
+/// counter-clock-wise order. Vertex normals point in the direction of their
+/// respective face (such that the shape appears faceted when lit). Vertex
+/// texture coordinates define a planar-projection on each face, such that the
+/// face fills the 0 to 1 range in both axes, with v running from the base to
+/// the apex of each side.
+pub struct PyramidBuilder {
+    options: ShapeOptions,
+    sides: usize,
+    base_radius: f32,
+    height: f32,
+    apex_offset: [f32; 2],
+}
+
+impl Default for PyramidBuilder {
+    fn default() -> Self {
+        PyramidBuilder {
+            options: ShapeOptions::default(),
+            sides: 4,
+            base_radius: 1.0,
+            height: 2.0,
+            apex_offset: [0.0, 0.0],
+        }
+    }
+}
+
+impl PyramidBuilder {
+    /// Create a new `PyramidBuilder` object.
+    pub fn new() -> PyramidBuilder {
+        Default::default()
+    }
+
+    /// Specify the number of sides of the base polygon. By default, the
+    /// builder will produce a square pyramid with 4 sides.
+    pub fn with_sides(mut self, sides: usize) -> Self {
+        self.sides = sides;
+        self
+    }
+
+    /// Specify the radius of the circle in which the base polygon is
+    /// inscribed. By default, the base radius is 1.
+    pub fn with_base_radius(mut self, radius: f32) -> Self {
+        self.base_radius = radius;
+        self
+    }
+
+    /// Specify the height of the pyramid, from its base to its apex. The
+    /// pyramid remains centred about y = 0. By default, the height is 2.
+    pub fn with_height(mut self, height: f32) -> Self {
+        self.height = height;
+        self
+    }
+
+    /// Specify the offset of the apex from the axis of the base, in the x and
+    /// z axes. A non-zero offset produces an oblique pyramid. By default, the
+    /// apex is located directly above the centre of the base.
+    pub fn with_apex_offset(mut self, x: f32, z: f32) -> Self {
+        self.apex_offset = [x, z];
+        self
+    }
+
+    /// Returns the total number of vertices in each side face (e.g. excluding the
+    /// base). The current implementation always returns 3.
+    pub fn num_vertices_per_side(&self) -> usize {
+        3
+    }
+
+    /// Returns the total number of vertices in the base of the pyramid.
+    pub fn num_vertices_per_base(&self) -> usize {
+        self.sides.saturating_sub(2) * 3
+    }
+}
+
+impl ShapeBuilder for PyramidBuilder {
+    fn options(&self) -> &ShapeOptions {
+        &self.options
+    }
+
+    fn options_mut(&mut self) -> &mut ShapeOptions {
+        &mut self.options
+    }
+
+    fn primitives(&self) -> index::PrimitiveType {
+        index::PrimitiveType::TrianglesList
+    }
+
+    fn build_unindexed_vertices(&self) -> Result<Vec<Vertex>, ShapeCreationError> {
+        // Ensure there are enough sides to produce a valid base, and that the
+        // dimensions of the pyramid are not degenerate.
+        if self.sides < 3 {
+            return Err(ShapeCreationError::NotEnoughDivisionsInU);
+        }
+
+        if self.base_radius <= 0.0 {
+            return Err(ShapeCreationError::InvalidRadius);
+        }
+
+        if self.height <= 0.0 {
+            return Err(ShapeCreationError::InvalidHeight);
+        }
+
+        // Build the corners of the base, offset by half a side such that the
+        // sides of a square pyramid are aligned to the x and z axes.
+        let angle = 2.0 * f32::consts::PI / self.sides as f32;
+        let base = (0..self.sides)
+            .map(|x| {
+                let theta = (x as f32 + 0.5) * angle;
+                Vector3::<f32>::new(
+                    theta.cos() * self.base_radius,
+                    self.height * -0.5,
+                    theta.sin() * self.base_radius,
+                )
+            })
+            .collect::<Vec<Vector3<f32>>>();
+
+        let apex = Vector3::<f32>::new(self.apex_offset[0], self.height * 0.5, self.apex_offset[1]);
+
+        // Compute the normal transformation matrix.
+        let normal_matrix = self.options.normal_matrix();
+
+        // Build vertex array.
+        let total_num_verts = self.num_unindexed_vertices();
+        let mut vertices = Vec::<Vertex>::with_capacity(total_num_verts);
+
+        let mut emit = |vpos: Vector3<f32>, normal: Vector3<f32>, texcoord: [f32; 2]| {
+            let pos = self.options.matrix * vpos.extend(1.0);
+            vertices.push(Vertex {
+                position: Point3::<f32>::from_homogeneous(pos).into(),
+                normal: (normal_matrix * normal).normalize().into(),
+                texcoord,
+            });
+        };
+
+        // Emit the sides. Each side starts along its base edge, such that u
+        // runs along the base and v runs up towards the apex.
+        for side in 0..self.sides {
+            let next = (side + 1) % self.sides;
+            build_polygon(&[base[next], base[side], apex], &mut emit);
+        }
+
+        // Emit the base.
+        build_polygon(&base, &mut emit);
+
+        assert!(vertices.len() == total_num_verts);
+        Ok(vertices)
+    }
+
+    fn num_unindexed_vertices(&self) -> usize {
+        self.num_vertices_per_side() * self.sides + self.num_vertices_per_base()
+    }
+}
+
+#[test]
+pub fn ensure_default_pyramid_has_square_base_and_apex_at_top() {
+    let vertices = PyramidBuilder::new()
+        .build_vertices()
+        .expect("Failed to build vertices");
+    let half_side = 0.5f32.sqrt();
+    for vertex in &vertices {
+        let pos = Vector3::<f32>::from(vertex.position);
+        if pos.y > 0.0 {
+            assert_ulps_eq!(pos, Vector3::<f32>::new(0.0, 1.0, 0.0));
+        } else {
+            assert_ulps_eq!(pos.y, -1.0);
+            assert_ulps_eq!(pos.x.abs(), half_side, epsilon = 1e-6);
+            assert_ulps_eq!(pos.z.abs(), half_side, epsilon = 1e-6);
+        }
+    }
+}
+
+#[test]
+pub fn ensure_default_pyramid_has_outward_facing_normals() {
+    let vertices = PyramidBuilder::new()
+        .scale(2.0, 2.0, 2.0)
+        .build_vertices()
+        .expect("Failed to build vertices");
+    for vertex in &vertices {
+        let position = Vector3::<f32>::from(vertex.position);
+        let normal = Vector3::<f32>::from(vertex.normal);
+        assert!(position.dot(normal) > 0.0);
+    }
+}
+
+#[test]
+pub fn ensure_default_pyramid_has_uvs_in_unit_range() {
+    let vertices = PyramidBuilder::new()
+        .build_vertices()
+        .expect("Failed to build vertices");
+    for vertex in &vertices {
+        assert!(vertex.texcoord[0] >= 0.0 && vertex.texcoord[0] <= 1.0);
+        assert!(vertex.texcoord[1] >= 0.0 && vertex.texcoord[1] <= 1.0);
+    }
+
+    // The v axis of each side runs from the base to the apex.
+    for vertex in &vertices[..4 * 3] {
+        let expected = if vertex.position[1] > 0.0 { 1.0 } else { 0.0 };
+        assert_ulps_eq!(vertex.texcoord[1], expected, epsilon = 1e-6);
+    }
+}
+
+#[test]
+pub fn ensure_default_pyramid_has_ccw_triangles() {
+    for builder in [
+        PyramidBuilder::new(),
+        PyramidBuilder::new()
+            .with_sides(7)
+            .with_apex_offset(0.5, -0.25),
+    ] {
+        let vertices = builder.build_vertices().expect("Failed to build vertices");
+        for chunk in vertices.chunks(3) {
+            let v0 = Vector3::<f32>::from(chunk[0].position);
+            let v1 = Vector3::<f32>::from(chunk[1].position);
+            let v2 = Vector3::<f32>::from(chunk[2].position);
+            let eyepos = v0 + Vector3::<f32>::from(chunk[0].normal);
+            let e0 = v1 - v0;
+            let e1 = v2 - v0;
+            let n = e0.cross(e1);
+            assert!(n.dot(v0 - eyepos) <= 0.0);
+            assert!(n.dot(v1 - eyepos) <= 0.0);
+            assert!(n.dot(v2 - eyepos) <= 0.0);
+        }
+    }
+}
+
+#[test]
+pub fn ensure_default_pyramid_has_faceted_normals() {
+    let vertices = PyramidBuilder::new()
+        .with_sides(5)
+        .build_vertices()
+        .expect("Failed to build vertices");
+    for chunk in vertices.chunks(3) {
+        let v0 = Vector3::<f32>::from(chunk[0].position);
+        let v1 = Vector3::<f32>::from(chunk[1].position);
+        let v2 = Vector3::<f32>::from(chunk[2].position);
+        let n0 = Vector3::<f32>::from(chunk[0].normal);
+        let n1 = Vector3::<f32>::from(chunk[1].normal);
+        let n2 = Vector3::<f32>::from(chunk[2].normal);
+        let n = (v1 - v0).cross(v2 - v0).normalize();
+        assert_ulps_eq!(n, n0, epsilon = 0.0001);
+        assert_ulps_eq!(n, n1, epsilon = 0.0001);
+        assert_ulps_eq!(n, n2, epsilon = 0.0001);
+    }
+}
+
+#[test]
+pub fn ensure_oblique_pyramid_has_offset_apex() {
+    let builder = PyramidBuilder::new()
+        .with_sides(6)
+        .with_base_radius(0.5)
+        .with_height(3.0)
+        .with_apex_offset(1.0, 2.0);
+    let vertices = builder.build_vertices().expect("Failed to build vertices");
+    assert_eq!(vertices.len(), 6 * 3 + 4 * 3);
+    for vertex in &vertices {
+        let pos = Vector3::<f32>::from(vertex.position);
+        if pos.y > 0.0 {
+            assert_ulps_eq!(pos, Vector3::<f32>::new(1.0, 1.5, 2.0));
+        } else {
+            assert_ulps_eq!(pos.y, -1.5);
+            assert_ulps_eq!(pos.x.hypot(pos.z), 0.5, epsilon = 1e-6);
+        }
+    }
+}
+
+#[test]
+pub fn ensure_pyramid_with_invalid_parameters_fails() {
+    match PyramidBuilder::new().with_sides(2).build_vertices() {
+        Err(ShapeCreationError::NotEnoughDivisionsInU) => {}
+        _ => panic!("Expected NotEnoughDivisionsInU"),
+    }
+    match PyramidBuilder::new().with_base_radius(0.0).build_vertices() {
+        Err(ShapeCreationError::InvalidRadius) => {}
+        _ => panic!("Expected InvalidRadius"),
+    }
+    match PyramidBuilder::new().with_height(-1.0).build_vertices() {
+        Err(ShapeCreationError::InvalidHeight) => {}
+        _ => panic!("Expected InvalidHeight"),
+    }
+}
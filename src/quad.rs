@@ -1,34 +1,23 @@
 //! A module for constructing polygonal quad shapes.
 
 extern crate cgmath;
+#[cfg(feature = "glium")]
 extern crate glium;
 
 use self::cgmath::*;
 use crate::errors::ShapeCreationError;
+use crate::index;
+#[cfg(feature = "glium")]
+use crate::mesh::Mesh;
+use crate::shape::{ShapeBuilder, ShapeOptions};
+use crate::tangent::TangentSpace;
 use crate::vertex::Vertex;
 
 /// A polygonal quad.
 ///
 /// This object is constructed using a `QuadBuilder` object.
-pub struct Quad {
-    vertices: glium::vertex::VertexBufferAny,
-}
-
-/// Allows a `Quad` object to be passed as a source of vertices.
-impl<'a> From<&'a Quad> for glium::vertex::VerticesSource<'a> {
-    fn from(quad: &'a Quad) -> glium::vertex::VerticesSource<'a> {
-        (&quad.vertices).into()
-    }
-}
-
-/// Allows a `Quad` object to be passed as a source of indices.
-impl<'a> Into<glium::index::IndicesSource<'a>> for &'a Quad {
-    fn into(self) -> glium::index::IndicesSource<'a> {
-        glium::index::IndicesSource::NoIndices {
-            primitives: glium::index::PrimitiveType::TriangleStrip,
-        }
-    }
-}
+#[cfg(feature = "glium")]
+pub type Quad = Mesh;
 
 /// Responsible for building and returning a `Quad` object.
 ///
@@ -45,13 +34,16 @@ impl<'a> Into<glium::index::IndicesSource<'a>> for &'a Quad {
 /// respective face (such that the shape appears faceted when lit). Vertex
 /// texture coordinates define a planar-projection on the face.
 pub struct QuadBuilder {
-    matrix: cgmath::Matrix4<f32>,
+    options: ShapeOptions,
 }
 
 impl Default for QuadBuilder {
-    fn default() -> QuadBuilder {
+    fn default() -> Self {
         QuadBuilder {
-            matrix: cgmath::Matrix4::<f32>::identity(),
+            options: ShapeOptions {
+                tangent_space: TangentSpace::Quad,
+                ..Default::default()
+            },
         }
     }
 }
@@ -61,105 +53,24 @@ impl QuadBuilder {
     pub fn new() -> QuadBuilder {
         Default::default()
     }
+}
 
-    /// Apply a scaling transformation to the shape.
-    ///
-    /// The `scale`, `translate`, and `rotate` functions accumulate, and are
-    /// not commutative. The transformation functions are intended to provide
-    /// flexibility in model-space. For per-instance world-space transformations,
-    /// one should prefer to share as few shapes as possible across multiple
-    /// instances, and instead rely on uniform constants in the shader and/or
-    /// instanced drawing.
-    pub fn scale(mut self, x: f32, y: f32, z: f32) -> Self {
-        self.matrix = cgmath::Matrix4::from_nonuniform_scale(x, y, z) * self.matrix;
-        self
-    }
-
-    /// Apply a translation transformation to the shape.
-    ///
-    /// The `scale`, `translate`, and `rotate` functions accumulate, and are
-    /// not commutative. The transformation functions are intended to provide
-    /// flexibility in model-space. For per-instance world-space transformations,
-    /// one should prefer to share as few shapes as possible across multiple
-    /// instances, and instead rely on uniform constants in the shader and/or
-    /// instanced drawing.
-    pub fn translate(mut self, x: f32, y: f32, z: f32) -> Self {
-        self.matrix = cgmath::Matrix4::from_translation([x, y, z].into()) * self.matrix;
-        self
-    }
-
-    /// Apply a rotation transformation to the shape about the x-axis.
-    ///
-    /// The `scale`, `translate`, and `rotate` functions accumulate, and are
-    /// not commutative. The transformation functions are intended to provide
-    /// flexibility in model-space. For per-instance world-space transformations,
-    /// one should prefer to share as few shapes as possible across multiple
-    /// instances, and instead rely on uniform constants in the shader and/or
-    /// instanced drawing.
-    pub fn rotate_x(mut self, radians: f32) -> Self {
-        self.matrix = cgmath::Matrix4::<f32>::from(cgmath::Matrix3::<f32>::from_angle_x(
-            cgmath::Rad::<f32>(radians),
-        )) * self.matrix;
-        self
-    }
-
-    /// Apply a rotation transformation to the shape about the y-axis.
-    ///
-    /// The `scale`, `translate`, and `rotate` functions accumulate, and are
-    /// not commutative. The transformation functions are intended to provide
-    /// flexibility in model-space. For per-instance world-space transformations,
-    /// one should prefer to share as few shapes as possible across multiple
-    /// instances, and instead rely on uniform constants in the shader and/or
-    /// instanced drawing.
-    pub fn rotate_y(mut self, radians: f32) -> Self {
-        self.matrix = cgmath::Matrix4::<f32>::from(cgmath::Matrix3::<f32>::from_angle_y(
-            cgmath::Rad::<f32>(radians),
-        )) * self.matrix;
-        self
+impl ShapeBuilder for QuadBuilder {
+    fn options(&self) -> &ShapeOptions {
+        &self.options
     }
 
-    /// Apply a rotation transformation to the shape about the z-axis.
-    ///
-    /// The `scale`, `translate`, and `rotate` functions accumulate, and are
-    /// not commutative. The transformation functions are intended to provide
-    /// flexibility in model-space. For per-instance world-space transformations,
-    /// one should prefer to share as few shapes as possible across multiple
-    /// instances, and instead rely on uniform constants in the shader and/or
-    /// instanced drawing.
-    pub fn rotate_z(mut self, radians: f32) -> Self {
-        self.matrix = cgmath::Matrix4::<f32>::from(cgmath::Matrix3::<f32>::from_angle_z(
-            cgmath::Rad::<f32>(radians),
-        )) * self.matrix;
-        self
+    fn options_mut(&mut self) -> &mut ShapeOptions {
+        &mut self.options
     }
 
-    /// Build a new `Quad` object.
-    pub fn build<F>(self, display: &F) -> Result<Quad, ShapeCreationError>
-    where
-        F: glium::backend::Facade,
-    {
-        let vertices =
-            glium::vertex::VertexBuffer::<Vertex>::new(display, &self.build_vertices()?)?;
-
-        Ok(Quad {
-            vertices: glium::vertex::VertexBufferAny::from(vertices),
-        })
+    fn primitives(&self) -> index::PrimitiveType {
+        index::PrimitiveType::TriangleStrip
     }
 
-    /// Build the Quad vertices and return them in a vector.
-    ///
-    /// Useful if you wish to do other things with the vertices besides constructing
-    /// a `Quad` object (e.g. unit testing, further processing, etc).
-    pub fn build_vertices(&self) -> Result<Vec<Vertex>, ShapeCreationError> {
+    fn build_unindexed_vertices(&self) -> Result<Vec<Vertex>, ShapeCreationError> {
         // Compute the normal transformation matrix.
-        let normal_matrix = Matrix3::<f32>::from_cols(
-            self.matrix.x.truncate(),
-            self.matrix.y.truncate(),
-            self.matrix.z.truncate(),
-        )
-        .invert()
-        .unwrap_or_else(Matrix3::<f32>::identity)
-        .transpose();
+        let normal_matrix = self.options.normal_matrix();
 
         // Build the vertices.
         let verts_per_quad = 4;
@@ -169,13 +80,17 @@ impl QuadBuilder {
             let position = Vector4::<f32>::new((u * 2.0) - 1.0, (v * 2.0) - 1.0, 0.0, 1.0);
             let normal = Vector3::<f32>::new(0.0, 0.0, -1.0);
             vertices.push(Vertex {
-                position: Point3::<f32>::from_homogeneous(self.matrix * position).into(),
+                position: Point3::<f32>::from_homogeneous(self.options.matrix * position).into(),
                 normal: (normal_matrix * normal).normalize().into(),
                 texcoord: [u, v],
             });
         }
         Ok(vertices)
     }
+
+    fn num_unindexed_vertices(&self) -> usize {
+        4
+    }
 }
 
 #[test]
@@ -0,0 +1,16 @@
+//! A module containing the shading options shared across shapes.
+
+/// Determines how vertex normals are generated for a shape.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Shading {
+    /// Vertex normals point in the direction of their respective face, such
+    /// that the shape appears faceted when lit.
+    #[default]
+    Faceted,
+
+    /// Vertex normals point in the direction of the underlying smooth
+    /// surface, such that the shape appears smooth when lit. Vertices that
+    /// are shared between faces are then identical, and so smooth shading
+    /// pairs well with indexed geometry.
+    Smooth,
+}
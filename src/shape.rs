@@ -0,0 +1,565 @@
+//! A module containing the trait shared by all shape builders.
+
+extern crate cgmath;
+#[cfg(feature = "glium")]
+extern crate glium;
+
+use self::cgmath::{InnerSpace, Matrix, SquareMatrix};
+use crate::errors::ShapeCreationError;
+use crate::index;
+#[cfg(feature = "glium")]
+use crate::mesh::Mesh;
+use crate::shading::Shading;
+use crate::tangent::TangentSpace;
+use crate::vertex::{FromShapeVertex, TangentVertex, Vertex};
+use crate::winding;
+
+/// The colour with which the vertices of a shape are tinted.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum VertexColor {
+    /// Every vertex has the same colour.
+    Uniform([f32; 4]),
+
+    /// Each vertex has the colour of its axis, as encoded in the v texture
+    /// coordinate of the vertices of `Axes`.
+    PerAxis([[f32; 4]; 3]),
+}
+
+impl VertexColor {
+    /// Returns the colour of the given vertex.
+    pub(crate) fn of(&self, vertex: &Vertex) -> [f32; 4] {
+        match *self {
+            VertexColor::Uniform(color) => color,
+            VertexColor::PerAxis(colors) => colors[(vertex.texcoord[1] as usize).min(2)],
+        }
+    }
+}
+
+/// The options shared by all shape builders, which control how the vertices of a
+/// shape are transformed, shaded, oriented, and converted into other vertex types.
+///
+/// Each builder holds a `ShapeOptions` object, and exposes it to the provided
+/// methods of `ShapeBuilder` through the `options` and `options_mut` methods.
+#[derive(Copy, Clone, Debug)]
+pub struct ShapeOptions {
+    pub(crate) matrix: cgmath::Matrix4<f32>,
+    pub(crate) shading: Shading,
+    pub(crate) indexed: bool,
+    pub(crate) tangents: bool,
+    pub(crate) tangent_space: TangentSpace,
+    pub(crate) color: Option<VertexColor>,
+    pub(crate) inward: bool,
+    pub(crate) cubemap: bool,
+}
+
+impl Default for ShapeOptions {
+    fn default() -> Self {
+        ShapeOptions {
+            matrix: cgmath::Matrix4::<f32>::identity(),
+            shading: Shading::Faceted,
+            indexed: false,
+            tangents: false,
+            tangent_space: TangentSpace::Computed,
+            color: None,
+            inward: false,
+            cubemap: false,
+        }
+    }
+}
+
+impl ShapeOptions {
+    /// Returns the transformation that has been applied to the shape.
+    pub fn matrix(&self) -> cgmath::Matrix4<f32> {
+        self.matrix
+    }
+
+    /// Returns the transformation to apply to the normals of the shape, which is
+    /// the inverse-transpose of the upper 3x3 portion of its transformation.
+    pub fn normal_matrix(&self) -> cgmath::Matrix3<f32> {
+        cgmath::Matrix3::<f32>::from_cols(
+            self.matrix.x.truncate(),
+            self.matrix.y.truncate(),
+            self.matrix.z.truncate(),
+        )
+        .invert()
+        .unwrap_or_else(cgmath::Matrix3::<f32>::identity)
+        .transpose()
+    }
+}
+
+/// The interface shared by all shape builders, which allows generic code to
+/// transform and build any kind of shape.
+///
+/// The transformation functions (`transform`, `scale`, `translate`, `rotate`, and
+/// so on) accumulate, and are not commutative. The transformation functions are
+/// intended to provide flexibility in model-space. For per-instance world-space
+/// transformations, one should prefer to share as few shapes as possible across
+/// multiple instances, and instead rely on uniform constants in the shader and/or
+/// instanced drawing.
+pub trait ShapeBuilder: Sized {
+    /// Returns the options shared by all shape builders.
+    fn options(&self) -> &ShapeOptions;
+
+    /// Returns the options shared by all shape builders, for modification.
+    fn options_mut(&mut self) -> &mut ShapeOptions;
+
+    /// Apply an arbitrary transformation matrix to the shape, after any
+    /// transformations that have already been applied.
+    fn transform(mut self, matrix: cgmath::Matrix4<f32>) -> Self {
+        let options = self.options_mut();
+        options.matrix = matrix * options.matrix;
+        self
+    }
+
+    /// Apply a scaling transformation to the shape. Negative scales mirror the
+    /// shape, in which case the winding order of its primitives is reversed, such
+    /// that front-facing primitives remain in counter-clock-wise order.
+    fn scale(self, x: f32, y: f32, z: f32) -> Self {
+        self.transform(cgmath::Matrix4::from_nonuniform_scale(x, y, z))
+    }
+
+    /// Apply a translation transformation to the shape.
+    fn translate(self, x: f32, y: f32, z: f32) -> Self {
+        self.transform(cgmath::Matrix4::from_translation([x, y, z].into()))
+    }
+
+    /// Apply a rotation transformation to the shape about the x-axis.
+    fn rotate_x(self, radians: f32) -> Self {
+        self.transform(cgmath::Matrix4::from_angle_x(cgmath::Rad::<f32>(radians)))
+    }
+
+    /// Apply a rotation transformation to the shape about the y-axis.
+    fn rotate_y(self, radians: f32) -> Self {
+        self.transform(cgmath::Matrix4::from_angle_y(cgmath::Rad::<f32>(radians)))
+    }
+
+    /// Apply a rotation transformation to the shape about the z-axis.
+    fn rotate_z(self, radians: f32) -> Self {
+        self.transform(cgmath::Matrix4::from_angle_z(cgmath::Rad::<f32>(radians)))
+    }
+
+    /// Apply a rotation transformation to the shape, described by a quaternion.
+    /// The quaternion is normalised before use.
+    fn rotate(self, rotation: cgmath::Quaternion<f32>) -> Self {
+        self.transform(cgmath::Matrix4::from(rotation.normalize()))
+    }
+
+    /// Apply a rotation transformation to the shape about an arbitrary axis. The
+    /// axis does not need to be normalised, but must not be zero.
+    fn rotate_axis_angle(self, axis: cgmath::Vector3<f32>, radians: f32) -> Self {
+        self.transform(cgmath::Matrix4::from_axis_angle(
+            axis.normalize(),
+            cgmath::Rad::<f32>(radians),
+        ))
+    }
+
+    /// Apply a rotation transformation to the shape, such that its negative z-axis
+    /// (e.g. the direction in which a default `Quad` faces) points in the given
+    /// direction, and its y-axis points as closely as possible towards `up`. This
+    /// matches the orientation of an OpenGL camera looking along `direction`. The
+    /// direction must not be zero, and should the up vector be zero or parallel to
+    /// it, the y-axis instead points towards an arbitrary perpendicular direction.
+    fn look_at(self, direction: cgmath::Vector3<f32>, up: cgmath::Vector3<f32>) -> Self {
+        let z = -direction.normalize();
+        let x = up.cross(z);
+        let x = if x.magnitude2() > f32::EPSILON * f32::EPSILON * up.magnitude2() {
+            x.normalize()
+        } else if z.y.abs() < 0.9 {
+            cgmath::Vector3::<f32>::unit_y().cross(z).normalize()
+        } else {
+            cgmath::Vector3::<f32>::unit_x().cross(z).normalize()
+        };
+        let y = z.cross(x);
+        self.transform(cgmath::Matrix4::from(cgmath::Matrix3::from_cols(x, y, z)))
+    }
+
+    /// Apply a shear transformation to the shape, where each argument names the
+    /// coordinate that is offset, followed by the coordinate it is offset by (e.g.
+    /// `xy` offsets the x coordinate of each vertex by `xy` times its y coordinate).
+    fn shear(self, xy: f32, xz: f32, yx: f32, yz: f32, zx: f32, zy: f32) -> Self {
+        // Note that the matrix is specified column by column.
+        #[rustfmt::skip]
+        let matrix = cgmath::Matrix4::new(
+            1.0, yx,  zx,  0.0,
+            xy,  1.0, zy,  0.0,
+            xz,  yz,  1.0, 0.0,
+            0.0, 0.0, 0.0, 1.0,
+        );
+        self.transform(matrix)
+    }
+
+    /// Specify that the shape should be built as indexed geometry, where vertices
+    /// shared between primitives are stored once and referenced by an index buffer.
+    /// By default, every vertex of every primitive is stored separately.
+    fn indexed(mut self) -> Self {
+        self.options_mut().indexed = true;
+        self
+    }
+
+    /// Specify that the shape should be built with tangents for tangent-space
+    /// normal mapping, in which case its vertices use the `TangentVertex` layout
+    /// (or the `ColorTangentVertex` layout when a colour is also requested). By
+    /// default, tangents are not generated.
+    ///
+    /// The tangents of spheres, quads, and cuboids are derived analytically from
+    /// their parameterisation, and those of all other shapes are computed from the
+    /// texture coordinates of their triangles (see `tangent::compute_tangents`).
+    fn with_tangents(mut self) -> Self {
+        self.options_mut().tangents = true;
+        self
+    }
+
+    /// Specify a colour with which to tint every vertex of the shape, in which case
+    /// its vertices use the `ColorVertex` layout (or the `ColorTangentVertex` layout
+    /// when tangents are also requested). By default, vertices are not coloured.
+    fn with_color(mut self, color: [f32; 4]) -> Self {
+        self.options_mut().color = Some(VertexColor::Uniform(color));
+        self
+    }
+
+    /// Specify that the shape should face inwards (e.g. for a skybox, or the
+    /// interior of a room), in which case the winding order of its primitives
+    /// and the direction of its normals are both reversed. By default, the shape
+    /// faces outwards.
+    fn flip_faces(mut self) -> Self {
+        self.options_mut().inward = true;
+        self
+    }
+
+    /// Specify that the shape should face inwards, in the same manner as
+    /// `flip_faces` (of which this is an alias).
+    fn inward_facing(self) -> Self {
+        self.flip_faces()
+    }
+
+    /// Specify that the shape should be built with cubemap texture coordinates, in
+    /// which case its vertices use the `CubemapVertex` layout, and can be used to
+    /// sample a `glium::texture::Cubemap` (e.g. when combined with `flip_faces` to
+    /// draw a skybox). By default, two-dimensional texture coordinates are used.
+    ///
+    /// As `CubemapVertex` has no tangent or colour attributes, `build` returns an
+    /// error if tangents or a colour are also requested.
+    fn with_cubemap_texcoords(mut self) -> Self {
+        self.options_mut().cubemap = true;
+        self
+    }
+
+    /// Returns the type of primitive that the vertices (or indices) returned by
+    /// the builder make up.
+    fn primitives(&self) -> index::PrimitiveType;
+
+    /// Build a new `Mesh` object.
+    #[cfg(feature = "glium")]
+    fn build<F>(self, display: &F) -> Result<Mesh, ShapeCreationError>
+    where
+        F: glium::backend::Facade,
+    {
+        let (vertices, indices) = index::build_buffers(
+            display,
+            oriented_vertices(&self)?,
+            self.options(),
+            self.primitives(),
+        )?;
+        Ok(Mesh::new(vertices, indices, self.primitives()))
+    }
+
+    /// Build a new `Mesh` object, whose vertex buffer holds vertices of the given
+    /// type rather than the type chosen by the builder options.
+    #[cfg(feature = "glium")]
+    fn build_as<V, F>(self, display: &F) -> Result<Mesh, ShapeCreationError>
+    where
+        V: FromShapeVertex + glium::Vertex + Send + 'static,
+        F: glium::backend::Facade,
+    {
+        let (vertices, indices) = index::build_buffers_as::<F, V>(
+            display,
+            oriented_vertices(&self)?,
+            self.options(),
+            self.primitives(),
+        )?;
+        Ok(Mesh::new(vertices, indices, self.primitives()))
+    }
+
+    /// Build the shape vertices and return them in a vector.
+    ///
+    /// Useful if you wish to do other things with the vertices besides constructing
+    /// a `Mesh` object (e.g. unit testing, further processing, etc). If the
+    /// builder is indexed, each unique vertex is only returned once, and should be
+    /// referenced using the indices returned by `build_indices`.
+    fn build_vertices(&self) -> Result<Vec<Vertex>, ShapeCreationError> {
+        Ok(index::vertices(
+            oriented_vertices(self)?,
+            self.options().indexed,
+        ))
+    }
+
+    /// Build the shape vertices as the given vertex type, and return them in a
+    /// vector. The vertices are indexed in the same manner as `build_vertices`.
+    fn build_vertices_as<V: FromShapeVertex>(&self) -> Result<Vec<V>, ShapeCreationError> {
+        Ok(index::vertices_as(
+            oriented_vertices(self)?,
+            self.options(),
+            self.primitives(),
+        ))
+    }
+
+    /// Build the shape vertices along with their tangents, and return them in a
+    /// vector. The vertices are indexed in the same manner as `build_vertices`.
+    fn build_tangent_vertices(&self) -> Result<Vec<TangentVertex>, ShapeCreationError> {
+        self.build_vertices_as::<TangentVertex>()
+    }
+
+    /// Build the shape indices and return them in a vector.
+    ///
+    /// The indices refer to the vertices returned by `build_vertices`. If the
+    /// builder is not indexed, every vertex is unique, and so the indices simply
+    /// count up from zero.
+    fn build_indices(&self) -> Result<Vec<u32>, ShapeCreationError> {
+        Ok(index::indices(
+            oriented_vertices(self)?,
+            self.options().indexed,
+        ))
+    }
+
+    /// Build the transformed vertices of every primitive of the shape, where every
+    /// vertex of every primitive is emitted separately, in counter-clock-wise order
+    /// before any transformation.
+    ///
+    /// This is the method that each builder implements to generate its shape. The
+    /// provided build methods then reverse the winding order of the primitives when
+    /// the transformation mirrors the shape (or when `flip_faces` is requested),
+    /// before indexing and converting the vertices.
+    fn build_unindexed_vertices(&self) -> Result<Vec<Vertex>, ShapeCreationError>;
+
+    /// Returns the number of vertices generated by `build_unindexed_vertices`
+    /// (one for every vertex of every primitive).
+    fn num_unindexed_vertices(&self) -> usize;
+
+    /// Returns the total number of vertices that will be generated by the builder
+    /// (i.e. the length of the list returned by `build_vertices`). When the
+    /// builder is indexed, the shape is built to count its unique vertices, and
+    /// zero is returned if it fails to build.
+    fn num_vertices(&self) -> usize {
+        if self.options().indexed {
+            self.build_vertices().map_or(0, |x| x.len())
+        } else {
+            self.num_unindexed_vertices()
+        }
+    }
+
+    /// Returns the total number of indices that will be generated by the builder,
+    /// which is one for every vertex of every primitive.
+    fn num_indices(&self) -> usize {
+        self.num_unindexed_vertices()
+    }
+}
+
+/// Build the unindexed vertices of a shape, with the winding order of their
+/// primitives reversed if the transformation mirrors the shape, and the shape
+/// turned inside-out if requested.
+fn oriented_vertices<B: ShapeBuilder>(builder: &B) -> Result<Vec<Vertex>, ShapeCreationError> {
+    let options = builder.options();
+    Ok(winding::orient_faces(
+        &options.matrix,
+        builder.primitives(),
+        options.inward,
+        builder.build_unindexed_vertices()?,
+    ))
+}
+
+#[cfg(test)]
+fn ensure_counts_match<B: ShapeBuilder>(builder: B) {
+    let vertices = builder.build_vertices().expect("Failed to build vertices");
+    let indices = builder.build_indices().expect("Failed to build indices");
+    assert_eq!(vertices.len(), builder.num_vertices());
+    assert_eq!(indices.len(), builder.num_indices());
+
+    // Indexed builders share vertices between primitives, but not indices.
+    let builder = builder.indexed();
+    let vertices = builder.build_vertices().expect("Failed to build vertices");
+    let indices = builder.build_indices().expect("Failed to build indices");
+    assert_eq!(vertices.len(), builder.num_vertices());
+    assert_eq!(indices.len(), builder.num_indices());
+    assert_eq!(indices.len(), builder.num_unindexed_vertices());
+}
+
+#[test]
+pub fn ensure_all_builders_report_their_counts() {
+    ensure_counts_match(crate::axes::AxesBuilder::new());
+    ensure_counts_match(crate::capsule::CapsuleBuilder::new());
+    ensure_counts_match(crate::cone::ConeBuilder::new());
+    ensure_counts_match(crate::cubesphere::CubeSphereBuilder::new());
+    ensure_counts_match(crate::cuboid::CuboidBuilder::new());
+    ensure_counts_match(crate::cylinder::CylinderBuilder::new());
+    ensure_counts_match(crate::disc::DiscBuilder::new());
+    ensure_counts_match(crate::icosphere::IcosphereBuilder::new());
+    ensure_counts_match(crate::plane::PlaneBuilder::new());
+    ensure_counts_match(crate::platonic::PlatonicBuilder::dodecahedron());
+    ensure_counts_match(crate::pyramid::PyramidBuilder::new());
+    ensure_counts_match(crate::quad::QuadBuilder::new());
+    ensure_counts_match(crate::sphere::SphereBuilder::new());
+    ensure_counts_match(crate::torus::TorusBuilder::new());
+}
+
+#[cfg(test)]
+fn ensure_options_are_shared<B: ShapeBuilder>(builder: B) {
+    let color = [0.25, 0.5, 0.75, 1.0];
+    let builder = builder.indexed().with_color(color).flip_faces();
+    assert!(builder.options().indexed);
+    assert!(builder.options().inward);
+    let vertices = builder
+        .build_vertices_as::<crate::vertex::ColorVertex>()
+        .expect("Failed to build vertices");
+    assert_eq!(vertices.len(), builder.build_vertices().unwrap().len());
+    assert!(vertices.iter().all(|x| x.color == color));
+}
+
+#[test]
+pub fn ensure_all_builders_share_options() {
+    ensure_options_are_shared(crate::axes::AxesBuilder::new());
+    ensure_options_are_shared(crate::capsule::CapsuleBuilder::new());
+    ensure_options_are_shared(crate::cone::ConeBuilder::new());
+    ensure_options_are_shared(crate::cubesphere::CubeSphereBuilder::new());
+    ensure_options_are_shared(crate::cuboid::CuboidBuilder::new());
+    ensure_options_are_shared(crate::cylinder::CylinderBuilder::new());
+    ensure_options_are_shared(crate::disc::DiscBuilder::new());
+    ensure_options_are_shared(crate::icosphere::IcosphereBuilder::new());
+    ensure_options_are_shared(crate::plane::PlaneBuilder::new());
+    ensure_options_are_shared(crate::platonic::PlatonicBuilder::dodecahedron());
+    ensure_options_are_shared(crate::pyramid::PyramidBuilder::new());
+    ensure_options_are_shared(crate::quad::QuadBuilder::new());
+    ensure_options_are_shared(crate::sphere::SphereBuilder::new());
+    ensure_options_are_shared(crate::torus::TorusBuilder::new());
+}
+
+#[test]
+pub fn ensure_transforms_are_applied_in_order() {
+    use self::cgmath::*;
+    let vertices = crate::quad::QuadBuilder::new()
+        .scale(2.0, 1.0, 1.0)
+        .rotate_z(std::f32::consts::FRAC_PI_2)
+        .translate(0.0, 0.0, 3.0)
+        .build_vertices()
+        .expect("Failed to build vertices");
+    // The first corner of the quad is (-1, -1, 0).
+    assert_abs_diff_eq!(
+        Vector3::<f32>::from(vertices[0].position),
+        Vector3::new(1.0, -2.0, 3.0),
+        epsilon = 0.0001
+    );
+}
+
+#[test]
+pub fn ensure_quaternion_and_axis_angle_rotations_match_euler_rotations() {
+    use self::cgmath::*;
+    let expected = crate::cuboid::CuboidBuilder::new()
+        .rotate_y(0.7)
+        .build_vertices()
+        .expect("Failed to build vertices");
+    let quaternion = crate::cuboid::CuboidBuilder::new()
+        .rotate(Quaternion::from_angle_y(Rad(0.7)) * 2.0)
+        .build_vertices()
+        .expect("Failed to build vertices");
+    let axis_angle = crate::cuboid::CuboidBuilder::new()
+        .rotate_axis_angle(Vector3::new(0.0, 3.0, 0.0), 0.7)
+        .build_vertices()
+        .expect("Failed to build vertices");
+    for ((a, b), c) in expected
+        .iter()
+        .zip(quaternion.iter())
+        .zip(axis_angle.iter())
+    {
+        for (a, b) in [(a, b), (a, c)].iter() {
+            assert_abs_diff_eq!(
+                Vector3::<f32>::from(a.position),
+                Vector3::<f32>::from(b.position),
+                epsilon = 0.0001
+            );
+            assert_abs_diff_eq!(
+                Vector3::<f32>::from(a.normal),
+                Vector3::<f32>::from(b.normal),
+                epsilon = 0.0001
+            );
+        }
+    }
+}
+
+#[test]
+pub fn ensure_look_at_points_quad_along_direction() {
+    use self::cgmath::*;
+    let direction = Vector3::new(1.0, 2.0, -3.0);
+    let vertices = crate::quad::QuadBuilder::new()
+        .look_at(direction, Vector3::unit_y())
+        .build_vertices()
+        .expect("Failed to build vertices");
+    for vertex in &vertices {
+        assert_abs_diff_eq!(
+            Vector3::<f32>::from(vertex.normal),
+            direction.normalize(),
+            epsilon = 0.0001
+        );
+    }
+
+    // The u texture axis remains horizontal.
+    let u_axis =
+        Vector3::<f32>::from(vertices[2].position) - Vector3::<f32>::from(vertices[0].position);
+    assert_abs_diff_eq!(u_axis.y, 0.0, epsilon = 0.0001);
+}
+
+#[test]
+pub fn ensure_look_at_handles_parallel_up_vectors() {
+    use self::cgmath::*;
+    for (direction, up) in &[
+        (Vector3::unit_y(), Vector3::unit_y()),
+        (-Vector3::unit_y(), Vector3::unit_y()),
+        (Vector3::new(0.0, 0.0, 2.0), Vector3::new(0.0, 0.0, -1.0)),
+        (Vector3::unit_x(), Vector3::zero()),
+    ] {
+        let vertices = crate::quad::QuadBuilder::new()
+            .look_at(*direction, *up)
+            .build_vertices()
+            .expect("Failed to build vertices");
+        for vertex in &vertices {
+            assert!(vertex.position.iter().all(|x| x.is_finite()));
+            assert_abs_diff_eq!(
+                Vector3::<f32>::from(vertex.normal),
+                direction.normalize(),
+                epsilon = 0.0001
+            );
+        }
+    }
+}
+
+#[test]
+pub fn ensure_sheared_normals_are_perpendicular_to_faces() {
+    use self::cgmath::*;
+    let vertices = crate::cuboid::CuboidBuilder::new()
+        .shear(0.5, 0.0, 0.0, -0.3, 0.2, 0.0)
+        .build_vertices()
+        .expect("Failed to build vertices");
+    for triangle in vertices.chunks(3) {
+        let p0 = Vector3::<f32>::from(triangle[0].position);
+        let p1 = Vector3::<f32>::from(triangle[1].position);
+        let p2 = Vector3::<f32>::from(triangle[2].position);
+        let normal = Vector3::<f32>::from(triangle[0].normal);
+        assert_abs_diff_eq!(normal.dot(p1 - p0), 0.0, epsilon = 0.0001);
+        assert_abs_diff_eq!(normal.dot(p2 - p0), 0.0, epsilon = 0.0001);
+        assert!(normal.dot((p1 - p0).cross(p2 - p0)) > 0.0);
+    }
+}
+
+#[test]
+pub fn ensure_shear_offsets_named_coordinates() {
+    use self::cgmath::*;
+    let vertices = crate::quad::QuadBuilder::new()
+        .shear(0.5, 0.0, 0.0, 0.0, 0.0, 0.25)
+        .build_vertices()
+        .expect("Failed to build vertices");
+    // The corner at (1, 1, 0) is offset in x by half its y, and in z by a quarter
+    // of its y.
+    assert_abs_diff_eq!(
+        Vector3::<f32>::from(vertices[3].position),
+        Vector3::new(1.5, 1.0, 0.25),
+        epsilon = 0.0001
+    );
+}
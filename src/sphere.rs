@@ -1,10 +1,17 @@
 //! A module for constructing sphere shapes.
 
 extern crate cgmath;
+#[cfg(feature = "glium")]
 extern crate glium;
 
 use self::cgmath::*;
 use crate::errors::ShapeCreationError;
+use crate::index;
+#[cfg(feature = "glium")]
+use crate::mesh::Mesh;
+use crate::shading::Shading;
+use crate::shape::{ShapeBuilder, ShapeOptions};
+use crate::tangent::TangentSpace;
 use crate::vertex::Vertex;
 
 use std::f32;
@@ -12,25 +19,8 @@ use std::f32;
 /// A polygonal `Sphere` object.
 ///
 /// This object is constructed using a `SphereBuilder` object.
-pub struct Sphere {
-    vertices: glium::vertex::VertexBufferAny,
-}
-
-/// Allows a `Sphere` object to be passed as a source of vertices.
-impl<'a> From<&'a Sphere> for glium::vertex::VerticesSource<'a> {
-    fn from(sphere: &'a Sphere) -> glium::vertex::VerticesSource<'a> {
-        (&sphere.vertices).into()
-    }
-}
-
-/// Allows a `Sphere` object to be passed as a source of indices.
-impl<'a> Into<glium::index::IndicesSource<'a>> for &'a Sphere {
-    fn into(self) -> glium::index::IndicesSource<'a> {
-        glium::index::IndicesSource::NoIndices {
-            primitives: glium::index::PrimitiveType::TrianglesList,
-        }
-    }
-}
+#[cfg(feature = "glium")]
+pub type Sphere = Mesh;
 
 /// Responsible for building and returning a `Sphere` object.
 ///
@@ -40,11 +30,12 @@ impl<'a> Into<glium::index::IndicesSource<'a>> for &'a Sphere {
 ///
 /// The resultant geometry is constructed to suit OpenGL defaults - assuming
 /// a right-handed coordinate system, front-facing polygons are defined in
-/// counter-clock-wise order. Vertex normals point in the direction of their
-/// respective face (such that the shape appears faceted when lit). Vertex
+/// counter-clock-wise order. By default, vertex normals point in the direction
+/// of their respective face (such that the shape appears faceted when lit),
+/// although smooth normals can be requested instead. Vertex
 /// texture coordinates define a spherical-projection on the object.
 pub struct SphereBuilder {
-    matrix: cgmath::Matrix4<f32>,
+    options: ShapeOptions,
     u_divisions: usize,
     v_divisions: usize,
 }
@@ -52,7 +43,10 @@ pub struct SphereBuilder {
 impl Default for SphereBuilder {
     fn default() -> Self {
         SphereBuilder {
-            matrix: cgmath::Matrix4::<f32>::identity(),
+            options: ShapeOptions {
+                tangent_space: TangentSpace::Sphere,
+                ..Default::default()
+            },
             u_divisions: 24,
             v_divisions: 12,
         }
@@ -74,95 +68,62 @@ impl SphereBuilder {
         self
     }
 
-    /// Apply a scaling transformation to the shape.
-    ///
-    /// The `scale`, `translate`, and `rotate` functions accumulate, and are
-    /// not commutative. The transformation functions are intended to provide
-    /// flexibility in model-space. For per-instance world-space transformations,
-    /// one should prefer to share as few shapes as possible across multiple
-    /// instances, and instead rely on uniform constants in the shader and/or
-    /// instanced drawing.
-    pub fn scale(mut self, x: f32, y: f32, z: f32) -> Self {
-        self.matrix = cgmath::Matrix4::from_nonuniform_scale(x, y, z) * self.matrix;
+    /// Specify how vertex normals are generated. By default, the builder will
+    /// use `Shading::Faceted`.
+    pub fn with_shading(mut self, shading: Shading) -> Self {
+        self.options.shading = shading;
         self
     }
 
-    /// Apply a translation transformation to the shape.
-    ///
-    /// The `scale`, `translate`, and `rotate` functions accumulate, and are
-    /// not commutative. The transformation functions are intended to provide
-    /// flexibility in model-space. For per-instance world-space transformations,
-    /// one should prefer to share as few shapes as possible across multiple
-    /// instances, and instead rely on uniform constants in the shader and/or
-    /// instanced drawing.
-    pub fn translate(mut self, x: f32, y: f32, z: f32) -> Self {
-        self.matrix = cgmath::Matrix4::from_translation([x, y, z].into()) * self.matrix;
-        self
+    /// Returns the number of caps in the resultant sphere geometry. The current implementation
+    /// will always return 2.
+    pub fn num_caps(&self) -> usize {
+        2
     }
 
-    /// Apply a rotation transformation to the shape about the x-axis.
-    ///
-    /// The `scale`, `translate`, and `rotate` functions accumulate, and are
-    /// not commutative. The transformation functions are intended to provide
-    /// flexibility in model-space. For per-instance world-space transformations,
-    /// one should prefer to share as few shapes as possible across multiple
-    /// instances, and instead rely on uniform constants in the shader and/or
-    /// instanced drawing.
-    pub fn rotate_x(mut self, radians: f32) -> Self {
-        self.matrix = cgmath::Matrix4::<f32>::from(cgmath::Matrix3::<f32>::from_angle_x(
-            cgmath::Rad::<f32>(radians),
-        )) * self.matrix;
-        self
+    /// Returns the number of vertices generated for each cap face. The current implementation
+    /// will always return 3.
+    pub fn num_vertices_per_cap_face(&self) -> usize {
+        3
     }
 
-    /// Apply a rotation transformation to the shape about the y-axis.
-    ///
-    /// The `scale`, `translate`, and `rotate` functions accumulate, and are
-    /// not commutative. The transformation functions are intended to provide
-    /// flexibility in model-space. For per-instance world-space transformations,
-    /// one should prefer to share as few shapes as possible across multiple
-    /// instances, and instead rely on uniform constants in the shader and/or
-    /// instanced drawing.
-    pub fn rotate_y(mut self, radians: f32) -> Self {
-        self.matrix = cgmath::Matrix4::<f32>::from(cgmath::Matrix3::<f32>::from_angle_y(
-            cgmath::Rad::<f32>(radians),
-        )) * self.matrix;
-        self
+    /// Returns the total number of vertices in each cap.
+    pub fn num_vertices_per_cap(&self) -> usize {
+        self.num_vertices_per_cap_face() * self.u_divisions
     }
 
-    /// Apply a rotation transformation to the shape about the z-axis.
-    ///
-    /// The `scale`, `translate`, and `rotate` functions accumulate, and are
-    /// not commutative. The transformation functions are intended to provide
-    /// flexibility in model-space. For per-instance world-space transformations,
-    /// one should prefer to share as few shapes as possible across multiple
-    /// instances, and instead rely on uniform constants in the shader and/or
-    /// instanced drawing.
-    pub fn rotate_z(mut self, radians: f32) -> Self {
-        self.matrix = cgmath::Matrix4::<f32>::from(cgmath::Matrix3::<f32>::from_angle_z(
-            cgmath::Rad::<f32>(radians),
-        )) * self.matrix;
-        self
+    /// Returns the number of vertical slices in the resultant sphere geometry. The resultant
+    /// value will depend on the number of v divisions specified on the builder.
+    pub fn num_slices(&self) -> usize {
+        self.v_divisions - self.num_caps()
     }
 
-    /// Build a new `Sphere` object.
-    pub fn build<F>(self, display: &F) -> Result<Sphere, ShapeCreationError>
-    where
-        F: glium::backend::Facade,
-    {
-        let vertices =
-            glium::vertex::VertexBuffer::<Vertex>::new(display, &self.build_vertices()?)?;
+    /// Returns the total number of vertices in each vertical slice face (e.g. excluding caps).
+    /// The current implementation will always return 6.
+    pub fn num_vertices_per_slice_face(&self) -> usize {
+        6
+    }
+
+    /// Returns the total number of vertices in each vertical slice.
+    pub fn num_vertices_per_slice(&self) -> usize {
+        self.num_vertices_per_slice_face() * self.u_divisions
+    }
+}
+
+impl ShapeBuilder for SphereBuilder {
+    fn options(&self) -> &ShapeOptions {
+        &self.options
+    }
+
+    fn options_mut(&mut self) -> &mut ShapeOptions {
+        &mut self.options
+    }
 
-        Ok(Sphere {
-            vertices: glium::vertex::VertexBufferAny::from(vertices),
-        })
+    fn primitives(&self) -> index::PrimitiveType {
+        index::PrimitiveType::TrianglesList
     }
 
-    /// Build the shape vertices and return them in a vector.
-    ///
-    /// Useful if you wish to do other things with the vertices besides constructing
-    /// a `Sphere` object (e.g. unit testing, further processing, etc).
-    pub fn build_vertices(&self) -> Result<Vec<Vertex>, ShapeCreationError> {
+    fn build_unindexed_vertices(&self) -> Result<Vec<Vertex>, ShapeCreationError> {
         // Ensure there are enough divisions in u and v to produce valid
         // sphere geometry
         if self.u_divisions < 3 {
@@ -173,140 +134,129 @@ impl SphereBuilder {
             return Err(ShapeCreationError::NotEnoughDivisionsInV);
         }
 
-        // Build lookup tables.
-        let u_angle = 2.0 * f32::consts::PI / self.u_divisions as f32;
-        let v_angle = f32::consts::PI / self.v_divisions as f32;
-
-        fn sin_cos(val: f32) -> [f32; 2] {
-            [val.sin(), val.cos()]
-        }
-
-        let u_tab = (0..=self.u_divisions)
-            .map(|x| sin_cos(((x % self.u_divisions) as f32) * u_angle))
-            .collect::<Vec<[f32; 2]>>();
-
-        let v_tab = (0..=self.v_divisions)
-            .map(|x| sin_cos((x as f32) * v_angle))
-            .collect::<Vec<[f32; 2]>>();
-
-        let indices = [0, 1, 2, 2, 1, 3];
-
         // Compute the normal transformation matrix.
-        let normal_matrix = Matrix3::<f32>::from_cols(
-            self.matrix.x.truncate(),
-            self.matrix.y.truncate(),
-            self.matrix.z.truncate(),
-        )
-        .invert()
-        .unwrap_or_else(Matrix3::<f32>::identity)
-        .transpose();
+        let normal_matrix = self.options.normal_matrix();
 
         // Build vertex array.
-        let total_num_verts = self.num_vertices();
+        let lat_long = LatLong::new(self.u_divisions, self.v_divisions);
+        let total_num_verts = self.num_unindexed_vertices();
         let mut vertices = Vec::<Vertex>::with_capacity(total_num_verts);
 
         for v in 0..self.v_divisions {
-            for u in 0..self.u_divisions {
-                // Compute slice vertices
-                let verts = [
-                    Vector3::<f32>::new(
-                        u_tab[u + 1][1] * v_tab[v][0],
-                        v_tab[v][1],
-                        u_tab[u + 1][0] * v_tab[v][0],
-                    ),
-                    Vector3::<f32>::new(
-                        u_tab[u + 1][1] * v_tab[v + 1][0],
-                        v_tab[v + 1][1],
-                        u_tab[u + 1][0] * v_tab[v + 1][0],
-                    ),
-                    Vector3::<f32>::new(
-                        u_tab[u][1] * v_tab[v][0],
-                        v_tab[v][1],
-                        u_tab[u][0] * v_tab[v][0],
-                    ),
-                    Vector3::<f32>::new(
-                        u_tab[u][1] * v_tab[v + 1][0],
-                        v_tab[v + 1][1],
-                        u_tab[u][0] * v_tab[v + 1][0],
-                    ),
-                ];
-
-                let lut_coords = [(u + 1, v), (u + 1, v + 1), (u, v), (u, v + 1)];
-
-                // Compute face index offset and count
-                let (offset, count) = if v == 0 {
-                    (3, 3)
-                } else if v == self.v_divisions - 1 {
-                    (0, 3)
-                } else {
-                    (0, 6)
+            lat_long.build_row(v, |vpos, normal, (u, v)| {
+                // The smooth normal of a unit-sphere is its position.
+                let normal = match self.options.shading {
+                    Shading::Faceted => normal,
+                    Shading::Smooth => vpos,
                 };
-
-                // Compute face normal
-                let v0 = &verts[indices[offset]];
-                let v1 = &verts[indices[offset + 1]];
-                let v2 = &verts[indices[offset + 2]];
-                let normal = (v1 - v0).cross(v2 - v0).normalize();
-
-                // Emit vertices.
-                for index in offset..offset + count {
-                    let vpos = &verts[indices[index]];
-                    let pos = self.matrix * vpos.extend(1.0);
-                    let (u, v) = lut_coords[indices[index]];
-                    vertices.push(Vertex {
-                        position: Point3::<f32>::from_homogeneous(pos).into(),
-                        normal: (normal_matrix * normal).normalize().into(),
-                        texcoord: [
-                            u as f32 / self.u_divisions as f32,
-                            v as f32 / self.v_divisions as f32,
-                        ],
-                    });
-                }
-            }
+                let pos = self.options.matrix * vpos.extend(1.0);
+                vertices.push(Vertex {
+                    position: Point3::<f32>::from_homogeneous(pos).into(),
+                    normal: (normal_matrix * normal).normalize().into(),
+                    texcoord: [
+                        u as f32 / self.u_divisions as f32,
+                        v as f32 / self.v_divisions as f32,
+                    ],
+                });
+            });
         }
 
         assert!(vertices.len() == total_num_verts);
         Ok(vertices)
     }
 
-    /// Returns the number of caps in the resultant sphere geometry. The current implementation
-    /// will always return 2.
-    pub fn num_caps(&self) -> usize {
-        2
+    fn num_unindexed_vertices(&self) -> usize {
+        (self.num_vertices_per_slice() * self.num_slices())
+            + (self.num_vertices_per_cap() * self.num_caps())
     }
+}
 
-    /// Returns the number of vertices generated for each cap face. The current implementation
-    /// will always return 3.
-    pub fn num_vertices_per_cap_face(&self) -> usize {
-        3
-    }
+/// The latitude/longitude tessellation of a unit-sphere, shared by all shapes
+/// with spherical sections.
+///
+/// The u coordinate runs around the y-axis (longitude), and the v coordinate
+/// runs from the top pole (v = 0) to the bottom pole (v = v_divisions).
+pub(crate) struct LatLong {
+    u_divisions: usize,
+    v_divisions: usize,
+    u_tab: Vec<[f32; 2]>,
+    v_tab: Vec<[f32; 2]>,
+}
 
-    /// Returns the total number of vertices in each cap.
-    pub fn num_vertices_per_cap(&self) -> usize {
-        self.num_vertices_per_cap_face() * self.u_divisions
-    }
+impl LatLong {
+    /// Build the lookup tables for the given number of divisions.
+    pub(crate) fn new(u_divisions: usize, v_divisions: usize) -> LatLong {
+        let u_angle = 2.0 * f32::consts::PI / u_divisions as f32;
+        let v_angle = f32::consts::PI / v_divisions as f32;
 
-    /// Returns the number of vertical slices in the resultant sphere geometry. The resultant
-    /// value will depend on the number of v divisions specified on the builder.
-    pub fn num_slices(&self) -> usize {
-        self.v_divisions - self.num_caps()
-    }
+        fn sin_cos(val: f32) -> [f32; 2] {
+            [val.sin(), val.cos()]
+        }
 
-    /// Returns the total number of vertices in each vertical slice face (e.g. excluding caps).
-    /// The current implementation will always return 6.
-    pub fn num_vertices_per_slice_face(&self) -> usize {
-        6
+        let u_tab = (0..=u_divisions)
+            .map(|x| sin_cos(((x % u_divisions) as f32) * u_angle))
+            .collect::<Vec<[f32; 2]>>();
+
+        let v_tab = (0..=v_divisions)
+            .map(|x| sin_cos((x as f32) * v_angle))
+            .collect::<Vec<[f32; 2]>>();
+
+        LatLong {
+            u_divisions,
+            v_divisions,
+            u_tab,
+            v_tab,
+        }
     }
 
-    /// Returns the total number of vertices in each vertical slice.
-    pub fn num_vertices_per_slice(&self) -> usize {
-        self.num_vertices_per_slice_face() * self.u_divisions
+    /// Returns the position of grid coordinate (u, v) on the unit-sphere.
+    pub(crate) fn point(&self, u: usize, v: usize) -> Vector3<f32> {
+        Vector3::<f32>::new(
+            self.u_tab[u][1] * self.v_tab[v][0],
+            self.v_tab[v][1],
+            self.u_tab[u][0] * self.v_tab[v][0],
+        )
     }
 
-    /// Returns the total number of vertices that will be generated by the builder.
-    pub fn num_vertices(&self) -> usize {
-        (self.num_vertices_per_slice() * self.num_slices())
-            + (self.num_vertices_per_cap() * self.num_caps())
+    /// Calls `emit` with the position, face normal, and grid coordinate of each
+    /// triangle vertex in row `v`, in counter-clock-wise order. The rows touching
+    /// the poles emit one triangle per face, and all other rows emit two.
+    pub(crate) fn build_row<F>(&self, v: usize, mut emit: F)
+    where
+        F: FnMut(Vector3<f32>, Vector3<f32>, (usize, usize)),
+    {
+        let indices = [0, 1, 2, 2, 1, 3];
+
+        // Compute face index offset and count
+        let (offset, count) = if v == 0 {
+            (3, 3)
+        } else if v == self.v_divisions - 1 {
+            (0, 3)
+        } else {
+            (0, 6)
+        };
+
+        for u in 0..self.u_divisions {
+            // Compute slice vertices
+            let lut_coords = [(u + 1, v), (u + 1, v + 1), (u, v), (u, v + 1)];
+            let verts = [
+                self.point(u + 1, v),
+                self.point(u + 1, v + 1),
+                self.point(u, v),
+                self.point(u, v + 1),
+            ];
+
+            // Compute face normal
+            let v0 = &verts[indices[offset]];
+            let v1 = &verts[indices[offset + 1]];
+            let v2 = &verts[indices[offset + 2]];
+            let normal = (v1 - v0).cross(v2 - v0).normalize();
+
+            // Emit vertices.
+            for index in &indices[offset..offset + count] {
+                emit(verts[*index], normal, lut_coords[*index]);
+            }
+        }
     }
 }
 
@@ -431,3 +381,129 @@ pub fn ensure_default_sphere_has_planar_quads() {
         }
     }
 }
+
+#[test]
+pub fn ensure_indexed_sphere_shares_vertices() {
+    let builder = SphereBuilder::new();
+    let expected = builder.build_vertices().expect("Failed to build vertices");
+    let builder = builder.indexed();
+    let vertices = builder.build_vertices().expect("Failed to build vertices");
+    let indices = builder.build_indices().expect("Failed to build indices");
+    assert_eq!(indices.len(), expected.len());
+    assert!(vertices.len() < expected.len());
+    for (index, vertex) in indices.iter().zip(expected.iter()) {
+        assert_eq!(vertices[*index as usize].position, vertex.position);
+        assert_eq!(vertices[*index as usize].normal, vertex.normal);
+        assert_eq!(vertices[*index as usize].texcoord, vertex.texcoord);
+    }
+}
+
+#[test]
+pub fn ensure_smooth_sphere_has_analytical_normals() {
+    let vertices = SphereBuilder::new()
+        .with_shading(Shading::Smooth)
+        .scale(2.0, 3.0, 4.0)
+        .rotate_y(0.7)
+        .rotate_x(0.3)
+        .build_vertices()
+        .expect("Failed to build vertices");
+
+    // The gradient of the implicit surface |M^-1 x| = 1 is M^-T M^-1 x.
+    let matrix = Matrix3::<f32>::from_angle_x(Rad(0.3))
+        * Matrix3::<f32>::from_angle_y(Rad(0.7))
+        * Matrix3::<f32>::from_diagonal(Vector3::<f32>::new(2.0, 3.0, 4.0));
+    let inverse = matrix.invert().expect("Failed to invert matrix");
+    for vertex in &vertices {
+        let position = Vector3::<f32>::from(vertex.position);
+        let expected = (inverse.transpose() * inverse * position).normalize();
+        assert_ulps_eq!(
+            Vector3::<f32>::from(vertex.normal),
+            expected,
+            epsilon = 0.0001
+        );
+    }
+}
+
+#[test]
+pub fn ensure_smooth_indexed_sphere_shares_vertices_between_faces() {
+    let faceted = SphereBuilder::new()
+        .indexed()
+        .build_vertices()
+        .expect("Failed to build vertices");
+    let smooth = SphereBuilder::new()
+        .with_shading(Shading::Smooth)
+        .indexed()
+        .build_vertices()
+        .expect("Failed to build vertices");
+
+    // Every grid point is shared, except for the poles (which have a distinct
+    // texture coordinate for each face) and the seam.
+    assert_eq!(smooth.len(), 2 * 24 + 25 * 11);
+    assert!(smooth.len() < faceted.len());
+}
+
+#[test]
+pub fn ensure_smooth_sphere_has_analytical_tangents() {
+    let vertices = SphereBuilder::new()
+        .with_shading(Shading::Smooth)
+        .with_tangents()
+        .build_tangent_vertices()
+        .expect("Failed to build vertices");
+    for vertex in &vertices {
+        // The tangent follows the lines of latitude everywhere (including the
+        // poles and the seam), at the longitude given by the u texture coordinate.
+        let theta = 2.0 * f32::consts::PI * vertex.texcoord[0];
+        let expected = Vector4::<f32>::new(-theta.sin(), 0.0, theta.cos(), 1.0);
+        assert_ulps_eq!(
+            Vector4::<f32>::from(vertex.tangent),
+            expected,
+            epsilon = 0.0001
+        );
+    }
+}
+
+#[test]
+pub fn ensure_flipped_sphere_has_inward_facing_normals() {
+    let vertices = SphereBuilder::new()
+        .scale(2.0, 2.0, 2.0)
+        .flip_faces()
+        .build_vertices()
+        .expect("Failed to build vertices");
+    for vertex in &vertices {
+        let position = Vector3::<f32>::from(vertex.position);
+        let normal = Vector3::<f32>::from(vertex.normal);
+        assert!(normal.dot(position) < 0.0);
+    }
+}
+
+#[test]
+pub fn ensure_flipped_sphere_faces_inwards() {
+    // Triangles are counter-clock-wise when viewed from the centre of the sphere.
+    let vertices = SphereBuilder::new()
+        .flip_faces()
+        .build_vertices()
+        .expect("Failed to build vertices");
+    for chunk in vertices.chunks(3) {
+        let v0 = Vector3::<f32>::from(chunk[0].position);
+        let v1 = Vector3::<f32>::from(chunk[1].position);
+        let v2 = Vector3::<f32>::from(chunk[2].position);
+        let n = (v1 - v0).cross(v2 - v0);
+        assert!(n.dot(v0 - Vector3::<f32>::zero()) <= 0.0);
+    }
+}
+
+#[test]
+pub fn ensure_cubemap_sphere_texcoords_are_unit_directions() {
+    let vertices = SphereBuilder::new()
+        .with_cubemap_texcoords()
+        .flip_faces()
+        .scale(50.0, 50.0, 50.0)
+        .build_vertices_as::<crate::vertex::CubemapVertex>()
+        .expect("Failed to build vertices");
+    for vertex in &vertices {
+        let direction = Vector3::<f32>::from(vertex.texcoord);
+        let position = Vector3::<f32>::from(vertex.position);
+        assert_abs_diff_eq!(direction.magnitude(), 1.0, epsilon = 0.0001);
+        assert_abs_diff_eq!(direction, position / 50.0, epsilon = 0.0001);
+    }
+}
@@ -0,0 +1,335 @@
+//! A module for generating the tangents of shape vertices.
+
+extern crate cgmath;
+
+use self::cgmath::*;
+use crate::cuboid::{corner_position, INDEX_LUT};
+use crate::index;
+use crate::shape::ShapeOptions;
+use crate::vertex::{TangentVertex, Vertex};
+
+use std::f32;
+
+/// How the tangents of a shape are generated.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum TangentSpace {
+    /// Tangents are computed from the texture coordinates of each triangle, using
+    /// `compute_tangents`.
+    Computed,
+
+    /// Tangents follow the x-axis of the unit-quad, facing -Z.
+    Quad,
+
+    /// Tangents follow the u texture coordinate of each side of the unit-cube, as
+    /// described by the cuboid `INDEX_LUT`.
+    Cuboid,
+
+    /// Tangents follow the lines of latitude of the unit-sphere, where the u and v
+    /// texture coordinates map to longitude and colatitude respectively.
+    Sphere,
+}
+
+/// Returns the tangents of a list of (unindexed) vertices that make up
+/// primitives of the given type, generated as requested by the given options.
+pub(crate) fn build_tangents(
+    vertices: &[Vertex],
+    options: &ShapeOptions,
+    primitives: index::PrimitiveType,
+) -> Vec<[f32; 4]> {
+    if let TangentSpace::Computed = options.tangent_space {
+        return compute_tangents(vertices, primitives)
+            .iter()
+            .map(|x| x.tangent)
+            .collect();
+    }
+
+    // Tangents are transformed by the builder matrix (in the same manner as
+    // positions), and their handedness flips whenever the normals are reversed
+    // relative to the geometry, either by a mirroring matrix or `flip_faces`.
+    let matrix = Matrix3::<f32>::from_cols(
+        options.matrix.x.truncate(),
+        options.matrix.y.truncate(),
+        options.matrix.z.truncate(),
+    );
+    let facing = if options.inward { -1.0 } else { 1.0 };
+    let sign = matrix.determinant().signum() * facing;
+
+    vertices
+        .iter()
+        .map(|vertex| {
+            let normal = Vector3::<f32>::from(vertex.normal);
+            let (tangent, bitangent, outward) = match options.tangent_space {
+                TangentSpace::Computed => unreachable!(),
+                TangentSpace::Quad => (
+                    Vector3::<f32>::unit_x(),
+                    Vector3::<f32>::unit_y(),
+                    -Vector3::<f32>::unit_z(),
+                ),
+                TangentSpace::Cuboid => {
+                    // Find the side of the unit-cube by undoing the normal
+                    // transformation (the inverse of the inverse-transpose).
+                    let outward = matrix.transpose() * normal * facing;
+                    let axis = (0..3)
+                        .max_by(|a, b| outward[*a].abs().partial_cmp(&outward[*b].abs()).unwrap())
+                        .unwrap_or(0);
+                    let side = axis * 2 + (outward[axis] > 0.0) as usize;
+                    let corner = |x: usize| corner_position(INDEX_LUT[side * 4 + x]);
+                    (corner(1) - corner(0), corner(2) - corner(0), outward)
+                }
+                TangentSpace::Sphere => {
+                    let (u_sin, u_cos) = (2.0 * f32::consts::PI * vertex.texcoord[0]).sin_cos();
+                    let (v_sin, v_cos) = (f32::consts::PI * vertex.texcoord[1]).sin_cos();
+                    (
+                        Vector3::new(-u_sin, 0.0, u_cos),
+                        Vector3::new(u_cos * v_cos, -v_sin, u_sin * v_cos),
+                        Vector3::new(u_cos * v_sin, v_cos, u_sin * v_sin),
+                    )
+                }
+            };
+            let handedness = if outward.cross(tangent).dot(bitangent) < 0.0 {
+                -sign
+            } else {
+                sign
+            };
+
+            // Orthogonalise the transformed tangent against the normal, which
+            // only differs from the analytic normal for faceted shapes.
+            let tangent = matrix * tangent;
+            let tangent = (tangent - normal * normal.dot(tangent)).normalize();
+            tangent.extend(handedness).into()
+        })
+        .collect()
+}
+
+/// Compute the tangents of a list of (unindexed) vertices that make up
+/// primitives of the given type, returning a new list of vertices (in the same
+/// order) that includes them.
+///
+/// For each triangle, the directions of increasing u and v texture coordinate
+/// are solved from its edges. These are weighted by the angle of each corner of
+/// the triangle, and accumulated across all vertices that share the same
+/// position, normal, and texture coordinate. The accumulated tangent is then
+/// projected onto the plane of the normal, and the w component is set to the
+/// sign of the accumulated bitangent. Unlike MikkTSpace, vertices are never
+/// split where the tangent frames of neighbouring triangles disagree, so the
+/// results may differ from those of MikkTSpace across texture seams and
+/// mirrored texture coordinates. As the vertices have already been transformed
+/// by the builder matrix, so too are the resultant tangents. For lines, the
+/// tangent runs along the direction of the line.
+pub fn compute_tangents(
+    vertices: &[Vertex],
+    primitives: index::PrimitiveType,
+) -> Vec<TangentVertex> {
+    // Group identical vertices together, such that their tangents are shared.
+    let (unique, indices) = index::deduplicate(vertices);
+    let mut tangents = vec![Vector3::<f32>::zero(); unique.len()];
+    let mut bitangents = vec![Vector3::<f32>::zero(); unique.len()];
+
+    let triangles = match primitives {
+        index::PrimitiveType::TrianglesList => indices
+            .chunks(3)
+            .map(|x| [x[0], x[1], x[2]])
+            .collect::<Vec<[u32; 3]>>(),
+        index::PrimitiveType::TriangleStrip => indices
+            .windows(3)
+            .map(|x| [x[0], x[1], x[2]])
+            .collect::<Vec<[u32; 3]>>(),
+        _ => Vec::new(),
+    };
+
+    for triangle in &triangles {
+        let corners = triangle.iter().map(|x| unique[*x as usize]);
+        let p = corners
+            .clone()
+            .map(|x| Vector3::<f32>::from(x.position))
+            .collect::<Vec<_>>();
+        let t = corners
+            .map(|x| Vector2::<f32>::from(x.texcoord))
+            .collect::<Vec<_>>();
+
+        // Solve for the directions of increasing u and v.
+        let (e0, e1) = (p[1] - p[0], p[2] - p[0]);
+        let (d0, d1) = (t[1] - t[0], t[2] - t[0]);
+        let det = d0.x * d1.y - d1.x * d0.y;
+        if det.abs() <= f32::EPSILON {
+            continue;
+        }
+        let tangent = (e0 * d1.y - e1 * d0.y) / det;
+        let bitangent = (e1 * d0.x - e0 * d1.x) / det;
+
+        // Weight the contribution to each corner by the angle of that corner.
+        for corner in 0..3 {
+            let a = p[(corner + 1) % 3] - p[corner];
+            let b = p[(corner + 2) % 3] - p[corner];
+            let weight = if a.magnitude2() > 0.0 && b.magnitude2() > 0.0 {
+                a.angle(b).0
+            } else {
+                0.0
+            };
+            tangents[triangle[corner] as usize] += tangent * weight;
+            bitangents[triangle[corner] as usize] += bitangent * weight;
+        }
+    }
+
+    if let index::PrimitiveType::LinesList = primitives {
+        for line in indices.chunks(2) {
+            let p0 = Vector3::<f32>::from(unique[line[0] as usize].position);
+            let p1 = Vector3::<f32>::from(unique[line[1] as usize].position);
+            tangents[line[0] as usize] += p1 - p0;
+            tangents[line[1] as usize] += p1 - p0;
+        }
+    }
+
+    // Orthogonalise the accumulated tangents, and compute their handedness.
+    let unique = unique
+        .iter()
+        .enumerate()
+        .map(|(index, vertex)| {
+            let normal = Vector3::<f32>::from(vertex.normal);
+            let tangent = if let index::PrimitiveType::LinesList = primitives {
+                tangents[index]
+            } else {
+                tangents[index] - normal * normal.dot(tangents[index])
+            };
+
+            // Fall back to an arbitrary tangent when the texture coordinates are
+            // degenerate (e.g. at the poles of a sphere).
+            let tangent = if tangent.magnitude2() > f32::EPSILON * f32::EPSILON {
+                tangent.normalize()
+            } else if normal.y.abs() < 0.9 {
+                normal.cross(Vector3::<f32>::unit_y()).normalize()
+            } else {
+                normal.cross(Vector3::<f32>::unit_x()).normalize()
+            };
+
+            let handedness = if normal.cross(tangent).dot(bitangents[index]) < 0.0 {
+                -1.0
+            } else {
+                1.0
+            };
+
+            TangentVertex {
+                position: vertex.position,
+                normal: vertex.normal,
+                texcoord: vertex.texcoord,
+                tangent: tangent.extend(handedness).into(),
+            }
+        })
+        .collect::<Vec<TangentVertex>>();
+
+    indices.iter().map(|x| unique[*x as usize]).collect()
+}
+
+#[cfg(test)]
+use crate::shape::ShapeBuilder;
+
+#[test]
+pub fn ensure_quad_tangents_follow_texture_u_axis() {
+    let vertices = crate::quad::QuadBuilder::new()
+        .build_vertices()
+        .expect("Failed to build vertices");
+    let vertices = compute_tangents(&vertices, index::PrimitiveType::TriangleStrip);
+    for vertex in &vertices {
+        // The quad faces -Z, and so its tangent-space is left-handed.
+        assert_ulps_eq!(
+            Vector4::<f32>::from(vertex.tangent),
+            Vector4::new(1.0, 0.0, 0.0, -1.0)
+        );
+    }
+}
+
+#[test]
+pub fn ensure_tangents_are_orthogonal_unit_vectors() {
+    let vertices = crate::torus::TorusBuilder::new()
+        .build_vertices()
+        .expect("Failed to build vertices");
+    let vertices = compute_tangents(&vertices, index::PrimitiveType::TrianglesList);
+    for vertex in &vertices {
+        let normal = Vector3::<f32>::from(vertex.normal);
+        let tangent = Vector4::<f32>::from(vertex.tangent);
+        assert_ulps_eq!(tangent.truncate().magnitude(), 1.0, epsilon = 0.0001);
+        assert!(normal.dot(tangent.truncate()).abs() < 0.0001);
+        assert!(tangent.w == 1.0 || tangent.w == -1.0);
+    }
+}
+
+#[test]
+pub fn ensure_shared_vertices_have_identical_tangents() {
+    let vertices = crate::sphere::SphereBuilder::new()
+        .with_shading(crate::shading::Shading::Smooth)
+        .build_vertices()
+        .expect("Failed to build vertices");
+    let tangents = compute_tangents(&vertices, index::PrimitiveType::TrianglesList);
+    for (a, ta) in vertices.iter().zip(tangents.iter()) {
+        for (b, tb) in vertices.iter().zip(tangents.iter()) {
+            if a.position == b.position && a.normal == b.normal && a.texcoord == b.texcoord {
+                assert_eq!(ta.tangent, tb.tangent);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+fn ensure_analytic_tangents_match_computed<B: ShapeBuilder>(builder: B) {
+    let vertices = builder.build_vertices().expect("Failed to build vertices");
+    let computed = compute_tangents(&vertices, builder.primitives());
+    let analytic = builder
+        .build_tangent_vertices()
+        .expect("Failed to build vertices");
+    for (a, b) in computed.iter().zip(analytic.iter()) {
+        assert_ulps_eq!(
+            Vector4::<f32>::from(a.tangent),
+            Vector4::<f32>::from(b.tangent),
+            epsilon = 0.0001
+        );
+    }
+}
+
+#[test]
+pub fn ensure_planar_tangents_are_exact() {
+    use crate::cuboid::CuboidBuilder;
+    use crate::quad::QuadBuilder;
+    ensure_analytic_tangents_match_computed(CuboidBuilder::new());
+    ensure_analytic_tangents_match_computed(CuboidBuilder::new().scale(-2.0, 3.0, 4.0));
+    ensure_analytic_tangents_match_computed(CuboidBuilder::new().rotate_x(0.5).flip_faces());
+    ensure_analytic_tangents_match_computed(QuadBuilder::new().rotate_z(0.5));
+    ensure_analytic_tangents_match_computed(QuadBuilder::new().scale(1.0, -2.0, 1.0));
+    ensure_analytic_tangents_match_computed(QuadBuilder::new().flip_faces());
+
+    let vertices = QuadBuilder::new()
+        .rotate_z(0.5)
+        .build_tangent_vertices()
+        .expect("Failed to build vertices");
+    for vertex in &vertices {
+        assert_ulps_eq!(
+            Vector4::<f32>::from(vertex.tangent),
+            Vector4::new(0.5f32.cos(), 0.5f32.sin(), 0.0, -1.0)
+        );
+    }
+}
+
+#[test]
+pub fn ensure_sphere_tangents_follow_lines_of_latitude() {
+    for inward in &[false, true] {
+        let builder = crate::sphere::SphereBuilder::new()
+            .with_shading(crate::shading::Shading::Smooth)
+            .rotate_x(0.5);
+        let builder = if *inward {
+            builder.flip_faces()
+        } else {
+            builder
+        };
+        let vertices = builder
+            .build_tangent_vertices()
+            .expect("Failed to build vertices");
+        let rotation = Matrix3::<f32>::from_angle_x(Rad(0.5));
+        for vertex in &vertices {
+            // The tangents at the poles follow the longitude of each vertex.
+            let angle = 2.0 * f32::consts::PI * vertex.texcoord[0];
+            let expected = rotation * Vector3::new(-angle.sin(), 0.0, angle.cos());
+            let tangent = Vector4::<f32>::from(vertex.tangent);
+            assert_ulps_eq!(tangent.truncate(), expected, epsilon = 0.0001);
+            assert_eq!(tangent.w, if *inward { -1.0 } else { 1.0 });
+        }
+    }
+}
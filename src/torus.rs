@@ -0,0 +1,383 @@
+//! A module for constructing torus shapes.
+
+extern crate cgmath;
+#[cfg(feature = "glium")]
+extern crate glium;
+
+use self::cgmath::*;
+use crate::errors::ShapeCreationError;
+use crate::index;
+#[cfg(feature = "glium")]
+use crate::mesh::Mesh;
+use crate::shading::Shading;
+use crate::shape::{ShapeBuilder, ShapeOptions};
+use crate::vertex::Vertex;
+
+use std::f32;
+
+/// A polygonal `Torus` object.
+///
+/// This object is constructed using a `TorusBuilder` object.
+#[cfg(feature = "glium")]
+pub type Torus = Mesh;
+
+/// Responsible for building and returning a `Torus` object.
+///
+/// By default, the torus has a major (ring) radius of 1 and a minor (tube)
+/// radius of 0.25, with its centre located at the origin and its ring lying
+/// in the xz-plane. The ring sweeps a full revolution about the y-axis,
+/// although a smaller sweep angle may be specified to produce a partial
+/// torus (whose ends are left open). This can be overriden using the
+/// transformation methods on this object.
+///
+/// The resultant geometry is constructed to suit OpenGL defaults - assuming
+/// a right-handed coordinate system, front-facing polygons are defined in
+/// counter-clock-wise order. By default, vertex normals point in the direction
+/// of their respective face (such that the shape appears faceted when lit),
+/// although smooth normals can be requested instead. Vertex
+/// texture coordinates wrap around the ring in the u direction, and around
+/// the tube in the v direction.
+pub struct TorusBuilder {
+    options: ShapeOptions,
+    major_radius: f32,
+    minor_radius: f32,
+    ring_divisions: usize,
+    tube_divisions: usize,
+    sweep: f32,
+}
+
+impl Default for TorusBuilder {
+    fn default() -> Self {
+        TorusBuilder {
+            options: ShapeOptions::default(),
+            major_radius: 1.0,
+            minor_radius: 0.25,
+            ring_divisions: 24,
+            tube_divisions: 12,
+            sweep: 2.0 * f32::consts::PI,
+        }
+    }
+}
+
+impl TorusBuilder {
+    /// Create a new `TorusBuilder` object.
+    pub fn new() -> TorusBuilder {
+        Default::default()
+    }
+
+    /// Specify the major radius (the distance from the centre of the torus to
+    /// the centre of its tube), and the minor radius (the radius of the tube).
+    /// By default, the builder will use a major radius of 1 and a minor radius
+    /// of 0.25.
+    pub fn with_radii(mut self, major: f32, minor: f32) -> Self {
+        self.major_radius = major;
+        self.minor_radius = minor;
+        self
+    }
+
+    /// Specify the number of divisions to make around the ring (u direction),
+    /// and around the tube (v direction). By default, the builder will use 24
+    /// ring divisions and 12 tube divisions.
+    pub fn with_divisions(mut self, ring: usize, tube: usize) -> Self {
+        self.ring_divisions = ring;
+        self.tube_divisions = tube;
+        self
+    }
+
+    /// Specify the angle (in radians) swept by the ring of the torus. By
+    /// default, the ring sweeps a full revolution (2 * PI), and larger angles
+    /// are clamped to it. The shape fails to build if the angle is not positive.
+    pub fn with_sweep(mut self, radians: f32) -> Self {
+        self.sweep = radians;
+        self
+    }
+
+    /// Specify how vertex normals are generated. By default, the builder will
+    /// use `Shading::Faceted`.
+    pub fn with_shading(mut self, shading: Shading) -> Self {
+        self.options.shading = shading;
+        self
+    }
+
+    /// Returns the total number of vertices in each face. The current implementation
+    /// will always return 6.
+    pub fn num_vertices_per_face(&self) -> usize {
+        6
+    }
+}
+
+impl ShapeBuilder for TorusBuilder {
+    fn options(&self) -> &ShapeOptions {
+        &self.options
+    }
+
+    fn options_mut(&mut self) -> &mut ShapeOptions {
+        &mut self.options
+    }
+
+    fn primitives(&self) -> index::PrimitiveType {
+        index::PrimitiveType::TrianglesList
+    }
+
+    fn build_unindexed_vertices(&self) -> Result<Vec<Vertex>, ShapeCreationError> {
+        // Ensure there are enough divisions in u and v, and that the radii
+        // produce valid torus geometry.
+        if self.ring_divisions < 3 {
+            return Err(ShapeCreationError::NotEnoughDivisionsInU);
+        }
+
+        if self.tube_divisions < 3 {
+            return Err(ShapeCreationError::NotEnoughDivisionsInV);
+        }
+
+        let radii = [self.major_radius, self.minor_radius];
+        if radii.iter().any(|x| x.is_nan() || *x <= 0.0) {
+            return Err(ShapeCreationError::InvalidRadius);
+        }
+
+        // Ensure the ring sweeps a positive angle (as a zero sweep would collapse
+        // every face, leaving no valid normals).
+        if self.sweep.is_nan() || self.sweep <= 0.0 {
+            return Err(ShapeCreationError::InvalidAngle);
+        }
+
+        // Build lookup tables. When the torus is closed, the last ring of
+        // vertices wraps around to exactly coincide with the first.
+        let full_sweep = 2.0 * f32::consts::PI;
+        let sweep = self.sweep.min(full_sweep);
+        let u_angle = sweep / self.ring_divisions as f32;
+        let v_angle = full_sweep / self.tube_divisions as f32;
+        let u_wrap = if sweep < full_sweep {
+            self.ring_divisions + 1
+        } else {
+            self.ring_divisions
+        };
+
+        fn sin_cos(val: f32) -> [f32; 2] {
+            [val.sin(), val.cos()]
+        }
+
+        let u_tab = (0..=self.ring_divisions)
+            .map(|x| sin_cos(((x % u_wrap) as f32) * u_angle))
+            .collect::<Vec<[f32; 2]>>();
+
+        let v_tab = (0..=self.tube_divisions)
+            .map(|x| sin_cos(((x % self.tube_divisions) as f32) * v_angle))
+            .collect::<Vec<[f32; 2]>>();
+
+        let indices = [0, 1, 2, 2, 1, 3];
+
+        // Compute the normal transformation matrix.
+        let normal_matrix = self.options.normal_matrix();
+
+        let point = |u: usize, v: usize| {
+            let radius = self.major_radius + self.minor_radius * v_tab[v][0];
+            Vector3::<f32>::new(
+                u_tab[u][1] * radius,
+                self.minor_radius * v_tab[v][1],
+                u_tab[u][0] * radius,
+            )
+        };
+
+        // Build vertex array.
+        let total_num_verts = self.num_unindexed_vertices();
+        let mut vertices = Vec::<Vertex>::with_capacity(total_num_verts);
+
+        for v in 0..self.tube_divisions {
+            for u in 0..self.ring_divisions {
+                // Compute face vertices
+                let verts = [
+                    point(u + 1, v),
+                    point(u + 1, v + 1),
+                    point(u, v),
+                    point(u, v + 1),
+                ];
+
+                let lut_coords = [(u + 1, v), (u + 1, v + 1), (u, v), (u, v + 1)];
+
+                // Compute face normal
+                let normal = (verts[1] - verts[0]).cross(verts[2] - verts[0]).normalize();
+
+                // Emit vertices.
+                for index in &indices {
+                    let pos = self.options.matrix * verts[*index].extend(1.0);
+                    let (u, v) = lut_coords[*index];
+                    let normal = match self.options.shading {
+                        Shading::Faceted => normal,
+                        Shading::Smooth => Vector3::<f32>::new(
+                            u_tab[u][1] * v_tab[v][0],
+                            v_tab[v][1],
+                            u_tab[u][0] * v_tab[v][0],
+                        ),
+                    };
+                    vertices.push(Vertex {
+                        position: Point3::<f32>::from_homogeneous(pos).into(),
+                        normal: (normal_matrix * normal).normalize().into(),
+                        texcoord: [
+                            u as f32 / self.ring_divisions as f32,
+                            v as f32 / self.tube_divisions as f32,
+                        ],
+                    });
+                }
+            }
+        }
+
+        assert!(vertices.len() == total_num_verts);
+        Ok(vertices)
+    }
+
+    fn num_unindexed_vertices(&self) -> usize {
+        self.num_vertices_per_face() * self.ring_divisions * self.tube_divisions
+    }
+}
+
+#[test]
+pub fn ensure_default_torus_has_correct_radii() {
+    let vertices = TorusBuilder::new()
+        .build_vertices()
+        .expect("Failed to build vertices");
+    for vertex in &vertices {
+        let pos = Vector3::<f32>::from(vertex.position);
+        let ring = Vector3::<f32>::new(pos.x, 0.0, pos.z).normalize();
+        assert_ulps_eq!((pos - ring).magnitude(), 0.25, epsilon = 0.0001);
+    }
+}
+
+#[test]
+pub fn ensure_default_torus_has_centroid_at_origin() {
+    let vertices = TorusBuilder::new()
+        .build_vertices()
+        .expect("Failed to build vertices");
+    let mut sum = Vector3::<f32>::zero();
+    for vertex in &vertices {
+        sum += Vector3::<f32>::from(vertex.position);
+    }
+    assert_ulps_eq!(sum, Vector3::<f32>::zero(), epsilon = 0.001);
+}
+
+#[test]
+pub fn ensure_default_torus_has_outward_facing_normals() {
+    let vertices = TorusBuilder::new()
+        .build_vertices()
+        .expect("Failed to build vertices");
+    for vertex in &vertices {
+        let pos = Vector3::<f32>::from(vertex.position);
+        let ring = Vector3::<f32>::new(pos.x, 0.0, pos.z).normalize();
+        let normal = Vector3::<f32>::from(vertex.normal);
+        assert!((pos - ring).dot(normal) > 0.0);
+    }
+}
+
+#[test]
+pub fn ensure_default_torus_has_uvs_in_unit_range() {
+    let vertices = TorusBuilder::new()
+        .with_divisions(4, 4)
+        .build_vertices()
+        .expect("Failed to build vertices");
+    for vertex in &vertices {
+        assert!(vertex.texcoord[0] >= 0.0);
+        assert!(vertex.texcoord[1] >= 0.0);
+        assert!(vertex.texcoord[0] <= 1.0);
+        assert!(vertex.texcoord[1] <= 1.0);
+    }
+}
+
+#[test]
+pub fn ensure_default_torus_has_ccw_triangles() {
+    let vertices = TorusBuilder::new()
+        .build_vertices()
+        .expect("Failed to build vertices");
+    for chunk in vertices.chunks(3) {
+        let v0 = Vector3::<f32>::from(chunk[0].position);
+        let v1 = Vector3::<f32>::from(chunk[1].position);
+        let v2 = Vector3::<f32>::from(chunk[2].position);
+        let eyepos = v0 + Vector3::<f32>::from(chunk[0].normal);
+        let e0 = v1 - v0;
+        let e1 = v2 - v0;
+        let n = e0.cross(e1);
+        assert!(n.dot(v0 - eyepos) <= 0.0);
+        assert!(n.dot(v1 - eyepos) <= 0.0);
+        assert!(n.dot(v2 - eyepos) <= 0.0);
+    }
+}
+
+#[test]
+pub fn ensure_default_torus_has_faceted_normals() {
+    let vertices = TorusBuilder::new()
+        .build_vertices()
+        .expect("Failed to build vertices");
+
+    for chunk in vertices.chunks(3) {
+        let v0 = Vector3::<f32>::from(chunk[0].position);
+        let v1 = Vector3::<f32>::from(chunk[1].position);
+        let v2 = Vector3::<f32>::from(chunk[2].position);
+        let n0 = Vector3::<f32>::from(chunk[0].normal);
+        let n1 = Vector3::<f32>::from(chunk[1].normal);
+        let n2 = Vector3::<f32>::from(chunk[2].normal);
+        let e0 = v1 - v0;
+        let e1 = v2 - v0;
+        let n = e0.cross(e1).normalize();
+        assert_ulps_eq!(n, n0, epsilon = 0.0001);
+        assert_ulps_eq!(n, n1, epsilon = 0.0001);
+        assert_ulps_eq!(n, n2, epsilon = 0.0001);
+    }
+}
+
+#[test]
+pub fn ensure_partial_torus_is_limited_to_sweep_angle() {
+    let vertices = TorusBuilder::new()
+        .with_sweep(f32::consts::PI / 2.0)
+        .build_vertices()
+        .expect("Failed to build vertices");
+    for vertex in &vertices {
+        assert!(vertex.position[0] >= -1e-6);
+        assert!(vertex.position[2] >= -1e-6);
+    }
+}
+
+#[test]
+pub fn ensure_torus_with_invalid_parameters_fails() {
+    match TorusBuilder::new().with_divisions(2, 3).build_vertices() {
+        Err(ShapeCreationError::NotEnoughDivisionsInU) => {}
+        _ => panic!("Expected NotEnoughDivisionsInU"),
+    }
+    match TorusBuilder::new().with_divisions(3, 2).build_vertices() {
+        Err(ShapeCreationError::NotEnoughDivisionsInV) => {}
+        _ => panic!("Expected NotEnoughDivisionsInV"),
+    }
+    match TorusBuilder::new().with_radii(1.0, 0.0).build_vertices() {
+        Err(ShapeCreationError::InvalidRadius) => {}
+        _ => panic!("Expected InvalidRadius"),
+    }
+    match TorusBuilder::new()
+        .with_radii(f32::NAN, 0.25)
+        .build_vertices()
+    {
+        Err(ShapeCreationError::InvalidRadius) => {}
+        _ => panic!("Expected InvalidRadius"),
+    }
+    for sweep in &[0.0, -1.0, f32::NAN] {
+        match TorusBuilder::new().with_sweep(*sweep).build_vertices() {
+            Err(ShapeCreationError::InvalidAngle) => {}
+            _ => panic!("Expected InvalidAngle"),
+        }
+    }
+}
+
+#[test]
+pub fn ensure_smooth_torus_has_normals_pointing_away_from_tube_centre() {
+    let vertices = TorusBuilder::new()
+        .with_shading(Shading::Smooth)
+        .build_vertices()
+        .expect("Failed to build vertices");
+    for vertex in &vertices {
+        let position = Vector3::<f32>::from(vertex.position);
+        let centre = Vector3::<f32>::new(position.x, 0.0, position.z).normalize();
+        let expected = (position - centre).normalize();
+        assert_ulps_eq!(
+            Vector3::<f32>::from(vertex.normal),
+            expected,
+            epsilon = 0.0001
+        );
+    }
+}
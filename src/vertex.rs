@@ -1,5 +1,6 @@
 //! A module containing the vertex structure shared across all shapes.
 
+#[cfg(feature = "glium")]
 extern crate glium;
 
 /// The vertex structure shared across all shapes.
@@ -10,4 +11,278 @@ pub struct Vertex {
     pub texcoord: [f32; 2],
 }
 
+#[cfg(feature = "glium")]
 implement_vertex!(Vertex, position, normal, texcoord);
+
+/// A vertex structure that extends `Vertex` with a tangent, for use with
+/// tangent-space normal mapping.
+///
+/// The xyz components of the tangent point in the direction of increasing u
+/// texture coordinate, and are orthogonal to the normal. The w component holds
+/// the handedness of the tangent-space (either 1 or -1), such that the
+/// bitangent can be reconstructed in a shader as `cross(normal, tangent.xyz) *
+/// tangent.w`.
+#[derive(Copy, Clone, Debug)]
+pub struct TangentVertex {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+    pub texcoord: [f32; 2],
+    pub tangent: [f32; 4],
+}
+
+#[cfg(feature = "glium")]
+implement_vertex!(TangentVertex, position, normal, texcoord, tangent);
+
+/// A vertex structure that extends `Vertex` with an RGBA colour, for use with
+/// tinted or vertex-painted shapes.
+#[derive(Copy, Clone, Debug)]
+pub struct ColorVertex {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+    pub texcoord: [f32; 2],
+    pub color: [f32; 4],
+}
+
+#[cfg(feature = "glium")]
+implement_vertex!(ColorVertex, position, normal, texcoord, color);
+
+/// A vertex structure that extends `Vertex` with both a tangent (as described by
+/// `TangentVertex`) and an RGBA colour (as described by `ColorVertex`).
+#[derive(Copy, Clone, Debug)]
+pub struct ColorTangentVertex {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+    pub texcoord: [f32; 2],
+    pub tangent: [f32; 4],
+    pub color: [f32; 4],
+}
+
+#[cfg(feature = "glium")]
+implement_vertex!(
+    ColorTangentVertex,
+    position,
+    normal,
+    texcoord,
+    tangent,
+    color
+);
+
+/// A vertex structure whose texture coordinate is a direction, for sampling a
+/// `glium::texture::Cubemap` (e.g. when drawing a skybox).
+///
+/// The texture coordinate is the direction from the centre of the shape to the
+/// vertex, before the shape was transformed by the builder. It is not normalised,
+/// as cubemap lookups do not require it.
+#[derive(Copy, Clone, Debug)]
+pub struct CubemapVertex {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+    pub texcoord: [f32; 3],
+}
+
+#[cfg(feature = "glium")]
+implement_vertex!(CubemapVertex, position, normal, texcoord);
+
+/// The attributes of a vertex generated by a shape builder, from which vertices
+/// of any type implementing `FromShapeVertex` are constructed.
+#[derive(Copy, Clone, Debug)]
+pub struct ShapeVertex {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+    pub texcoord: [f32; 2],
+
+    /// The tangent of the vertex, as described by `TangentVertex`. This is only
+    /// available when the vertex type requires tangents, or the builder was
+    /// asked to generate them.
+    pub tangent: Option<[f32; 4]>,
+
+    /// The RGBA colour of the vertex. This is only available when the builder
+    /// was given a colour.
+    pub color: Option<[f32; 4]>,
+
+    /// The direction from the centre of the shape to the vertex, before the shape
+    /// was transformed by the builder (e.g. for sampling a cubemap).
+    pub direction: [f32; 3],
+}
+
+/// A trait for vertex types that can be built by the shape builders, allowing
+/// shapes to be built directly into a user-defined vertex structure (e.g. using
+/// the `build_as` and `build_vertices_as` builder methods).
+pub trait FromShapeVertex: Copy {
+    /// Whether the vertex type requires tangents, in which case the builders
+    /// will always generate them.
+    const REQUIRES_TANGENTS: bool = false;
+
+    /// Construct a vertex from the attributes generated by a shape builder.
+    fn from_shape_vertex(vertex: &ShapeVertex) -> Self;
+}
+
+impl FromShapeVertex for Vertex {
+    fn from_shape_vertex(vertex: &ShapeVertex) -> Self {
+        Vertex {
+            position: vertex.position,
+            normal: vertex.normal,
+            texcoord: vertex.texcoord,
+        }
+    }
+}
+
+impl FromShapeVertex for TangentVertex {
+    const REQUIRES_TANGENTS: bool = true;
+
+    fn from_shape_vertex(vertex: &ShapeVertex) -> Self {
+        TangentVertex {
+            position: vertex.position,
+            normal: vertex.normal,
+            texcoord: vertex.texcoord,
+            tangent: vertex.tangent.unwrap_or([1.0, 0.0, 0.0, 1.0]),
+        }
+    }
+}
+
+impl FromShapeVertex for ColorVertex {
+    fn from_shape_vertex(vertex: &ShapeVertex) -> Self {
+        ColorVertex {
+            position: vertex.position,
+            normal: vertex.normal,
+            texcoord: vertex.texcoord,
+            color: vertex.color.unwrap_or([1.0, 1.0, 1.0, 1.0]),
+        }
+    }
+}
+
+impl FromShapeVertex for CubemapVertex {
+    fn from_shape_vertex(vertex: &ShapeVertex) -> Self {
+        CubemapVertex {
+            position: vertex.position,
+            normal: vertex.normal,
+            texcoord: vertex.direction,
+        }
+    }
+}
+
+impl FromShapeVertex for ColorTangentVertex {
+    const REQUIRES_TANGENTS: bool = true;
+
+    fn from_shape_vertex(vertex: &ShapeVertex) -> Self {
+        ColorTangentVertex {
+            position: vertex.position,
+            normal: vertex.normal,
+            texcoord: vertex.texcoord,
+            tangent: vertex.tangent.unwrap_or([1.0, 0.0, 0.0, 1.0]),
+            color: vertex.color.unwrap_or([1.0, 1.0, 1.0, 1.0]),
+        }
+    }
+}
+
+#[cfg(test)]
+#[derive(Copy, Clone, Debug)]
+struct PositionVertex {
+    position: [f32; 3],
+}
+
+#[cfg(test)]
+use crate::shape::ShapeBuilder;
+
+#[cfg(test)]
+impl FromShapeVertex for PositionVertex {
+    fn from_shape_vertex(vertex: &ShapeVertex) -> Self {
+        PositionVertex {
+            position: vertex.position,
+        }
+    }
+}
+
+#[test]
+pub fn ensure_custom_vertices_match_default_vertices() {
+    let builder = crate::cuboid::CuboidBuilder::new().indexed();
+    let vertices = builder.build_vertices().expect("Failed to build vertices");
+    let custom = builder
+        .build_vertices_as::<PositionVertex>()
+        .expect("Failed to build vertices");
+    assert_eq!(vertices.len(), custom.len());
+    for (vertex, other) in vertices.iter().zip(custom.iter()) {
+        assert_eq!(vertex.position, other.position);
+    }
+}
+
+#[cfg(test)]
+fn build_positions<B: ShapeBuilder>(builder: &B) -> Vec<PositionVertex> {
+    builder
+        .build_vertices_as::<PositionVertex>()
+        .expect("Failed to build vertices")
+}
+
+#[test]
+pub fn ensure_generic_builders_build_custom_vertices() {
+    let sphere = crate::sphere::SphereBuilder::new();
+    let axes = crate::axes::AxesBuilder::new().indexed();
+    for (custom, vertices) in [
+        (build_positions(&sphere), sphere.build_vertices()),
+        (build_positions(&axes), axes.build_vertices()),
+    ]
+    .iter()
+    {
+        let vertices = vertices.as_ref().expect("Failed to build vertices");
+        assert_eq!(custom.len(), vertices.len());
+        for (vertex, other) in vertices.iter().zip(custom.iter()) {
+            assert_eq!(vertex.position, other.position);
+        }
+    }
+}
+
+#[test]
+pub fn ensure_tangent_vertices_always_have_tangents() {
+    // Tangents are generated for `TangentVertex` even if not requested.
+    let vertices = crate::quad::QuadBuilder::new()
+        .build_vertices_as::<TangentVertex>()
+        .expect("Failed to build vertices");
+    for vertex in &vertices {
+        assert_eq!(vertex.tangent, [1.0, 0.0, 0.0, -1.0]);
+    }
+}
+
+#[test]
+pub fn ensure_shape_vertices_only_have_requested_tangents() {
+    #[derive(Copy, Clone)]
+    struct HasTangent(bool);
+    impl FromShapeVertex for HasTangent {
+        fn from_shape_vertex(vertex: &ShapeVertex) -> Self {
+            HasTangent(vertex.tangent.is_some())
+        }
+    }
+
+    let builder = crate::sphere::SphereBuilder::new();
+    let vertices = builder
+        .build_vertices_as::<HasTangent>()
+        .expect("Failed to build vertices");
+    assert!(vertices.iter().all(|x| !x.0));
+    let vertices = builder
+        .with_tangents()
+        .build_vertices_as::<HasTangent>()
+        .expect("Failed to build vertices");
+    assert!(vertices.iter().all(|x| x.0));
+}
+
+#[test]
+pub fn ensure_tinted_vertices_have_uniform_color() {
+    let color = [0.25, 0.5, 0.75, 1.0];
+    let vertices = crate::cuboid::CuboidBuilder::new()
+        .with_color(color)
+        .with_tangents()
+        .build_vertices_as::<ColorTangentVertex>()
+        .expect("Failed to build vertices");
+    for vertex in &vertices {
+        assert_eq!(vertex.color, color);
+    }
+}
+
+#[test]
+pub fn ensure_untinted_vertices_are_white() {
+    let vertices = crate::cuboid::CuboidBuilder::new()
+        .build_vertices_as::<ColorVertex>()
+        .expect("Failed to build vertices");
+    for vertex in &vertices {
+        assert_eq!(vertex.color, [1.0, 1.0, 1.0, 1.0]);
+    }
+}
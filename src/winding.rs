@@ -0,0 +1,165 @@
+//! A module for controlling the winding order of shape primitives.
+
+extern crate cgmath;
+
+use self::cgmath::*;
+use crate::index;
+use crate::vertex::Vertex;
+
+/// Returns true if the given transformation mirrors geometry (e.g. a negative
+/// scale along one axis), which reverses the winding order of its primitives.
+pub(crate) fn is_mirrored(matrix: &Matrix4<f32>) -> bool {
+    matrix.determinant() < 0.0
+}
+
+/// Reverse the winding order of a list of primitives of the given type, which
+/// may be either vertices or indices.
+///
+/// The vertices of each triangle in a list are reordered in place. A strip has
+/// each pair of vertices swapped instead, which zig-zags between the same two
+/// rows of vertices (e.g. the sides of a `Quad`) in the opposite order, such that
+/// the same area is covered with the opposite winding. Lines have no winding, and
+/// are left unchanged.
+pub(crate) fn flip_winding<T>(primitives: index::PrimitiveType, items: &mut [T]) {
+    match primitives {
+        index::PrimitiveType::TrianglesList => {
+            for triangle in items.chunks_mut(3) {
+                triangle.swap(1, 2);
+            }
+        }
+        index::PrimitiveType::TriangleStrip => {
+            for pair in items.chunks_exact_mut(2) {
+                pair.swap(0, 1);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Orient the faces of a list of (unindexed) vertices built using the given
+/// transformation, such that front-facing primitives remain in counter-clock-wise
+/// order even when the transformation mirrors geometry. When `inward` is set, the
+/// shape is turned inside-out instead, by reversing both the winding order of its
+/// primitives and the direction of its normals.
+pub(crate) fn orient_faces(
+    matrix: &Matrix4<f32>,
+    primitives: index::PrimitiveType,
+    inward: bool,
+    mut vertices: Vec<Vertex>,
+) -> Vec<Vertex> {
+    if is_mirrored(matrix) != inward {
+        flip_winding(primitives, &mut vertices);
+    }
+    if inward {
+        flip_normals(&mut vertices);
+    }
+    vertices
+}
+
+/// Reverse the direction of the normals of a list of vertices.
+pub(crate) fn flip_normals(vertices: &mut [Vertex]) {
+    for vertex in vertices {
+        vertex.normal = (-Vector3::<f32>::from(vertex.normal)).into();
+    }
+}
+
+#[cfg(test)]
+use crate::shape::ShapeBuilder;
+
+#[cfg(test)]
+fn ensure_ccw_triangles<B: ShapeBuilder>(builder: B) {
+    let vertices = builder.build_vertices().expect("Failed to build vertices");
+    let indices = builder.build_indices().expect("Failed to build indices");
+    for triangle in indices.chunks(3) {
+        let v0 = Vector3::<f32>::from(vertices[triangle[0] as usize].position);
+        let v1 = Vector3::<f32>::from(vertices[triangle[1] as usize].position);
+        let v2 = Vector3::<f32>::from(vertices[triangle[2] as usize].position);
+        let eyepos = v0 + Vector3::<f32>::from(vertices[triangle[0] as usize].normal);
+        let e0 = v1 - v0;
+        let e1 = v2 - v0;
+        let n = e0.cross(e1);
+        assert!(n.dot(v0 - eyepos) <= 0.0);
+        assert!(n.dot(v1 - eyepos) <= 0.0);
+        assert!(n.dot(v2 - eyepos) <= 0.0);
+    }
+}
+
+#[test]
+pub fn ensure_mirrored_shapes_have_ccw_triangles() {
+    ensure_ccw_triangles(crate::capsule::CapsuleBuilder::new().scale(-1.0, 1.0, 1.0));
+    ensure_ccw_triangles(crate::cone::ConeBuilder::new().scale(1.0, -1.0, 1.0));
+    ensure_ccw_triangles(crate::cubesphere::CubeSphereBuilder::new().scale(1.0, 1.0, -1.0));
+    ensure_ccw_triangles(crate::cuboid::CuboidBuilder::new().scale(-2.0, 3.0, 4.0));
+    ensure_ccw_triangles(crate::cylinder::CylinderBuilder::new().scale(-1.0, 1.0, 1.0));
+    ensure_ccw_triangles(crate::disc::DiscBuilder::new().scale(-1.0, 1.0, 1.0));
+    ensure_ccw_triangles(crate::icosphere::IcosphereBuilder::new().scale(-1.0, -1.0, -1.0));
+    ensure_ccw_triangles(crate::plane::PlaneBuilder::new().scale(-1.0, 1.0, 1.0));
+    ensure_ccw_triangles(crate::platonic::PlatonicBuilder::dodecahedron().scale(-1.0, 1.0, 1.0));
+    ensure_ccw_triangles(crate::pyramid::PyramidBuilder::new().scale(1.0, -1.0, 1.0));
+    ensure_ccw_triangles(crate::sphere::SphereBuilder::new().scale(1.0, 1.0, -1.0));
+    ensure_ccw_triangles(crate::torus::TorusBuilder::new().scale(-1.0, 1.0, 1.0));
+}
+
+#[test]
+pub fn ensure_mirrored_indexed_shapes_have_ccw_triangles() {
+    ensure_ccw_triangles(
+        crate::cuboid::CuboidBuilder::new()
+            .indexed()
+            .scale(-1.0, 1.0, 1.0),
+    );
+    ensure_ccw_triangles(
+        crate::sphere::SphereBuilder::new()
+            .indexed()
+            .scale(-1.0, 1.0, 1.0),
+    );
+}
+
+#[test]
+pub fn ensure_mirrored_quad_has_ccw_triangles() {
+    let vertices = crate::quad::QuadBuilder::new()
+        .scale(-1.0, 1.0, 1.0)
+        .build_vertices()
+        .expect("Failed to build vertices");
+    let tris = [[0, 1, 2], [2, 1, 3]];
+    for tri in tris.iter() {
+        let v0 = Vector3::<f32>::from(vertices[tri[0]].position);
+        let v1 = Vector3::<f32>::from(vertices[tri[1]].position);
+        let v2 = Vector3::<f32>::from(vertices[tri[2]].position);
+        let eyepos = v0 + Vector3::<f32>::from(vertices[tri[0]].normal);
+        let n = (v1 - v0).cross(v2 - v0);
+        assert!(n.dot(v0 - eyepos) < 0.0);
+    }
+}
+
+#[test]
+pub fn ensure_double_mirroring_preserves_winding() {
+    // Mirroring twice is a rotation, and so the winding is left untouched.
+    let expected = crate::cuboid::CuboidBuilder::new()
+        .rotate_z(std::f32::consts::PI)
+        .build_vertices()
+        .expect("Failed to build vertices");
+    let vertices = crate::cuboid::CuboidBuilder::new()
+        .scale(-1.0, -1.0, 1.0)
+        .build_vertices()
+        .expect("Failed to build vertices");
+    for (a, b) in expected.iter().zip(vertices.iter()) {
+        assert_abs_diff_eq!(
+            Vector3::<f32>::from(a.position),
+            Vector3::<f32>::from(b.position),
+            epsilon = 0.0001
+        );
+    }
+}
+
+#[test]
+pub fn ensure_flipped_shapes_have_ccw_triangles() {
+    // Flipped shapes remain consistent, as their normals are reversed too.
+    ensure_ccw_triangles(crate::cuboid::CuboidBuilder::new().flip_faces());
+    ensure_ccw_triangles(crate::plane::PlaneBuilder::new().flip_faces());
+    ensure_ccw_triangles(crate::sphere::SphereBuilder::new().flip_faces().indexed());
+    ensure_ccw_triangles(
+        crate::torus::TorusBuilder::new()
+            .flip_faces()
+            .scale(-1.0, 1.0, 1.0),
+    );
+}